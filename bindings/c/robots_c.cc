@@ -21,18 +21,388 @@
 #include "robots_c.h"
 #include "robots.h"
 
+#include <algorithm>
+#include <cctype>
+#include <chrono>
+#include <cstdint>
+#include <memory>
+#include <optional>
 #include <string>
 #include <string_view>
+#include <utility>
 #include <vector>
 
 #define ROBOTS_VERSION "1.1.0"
 
+namespace googlebot {
+// Defined in robots.cc, outside the anonymous namespace.
+std::string GetPathParamsQuery(const std::string& url);
+}  // namespace googlebot
+
 // =============================================================================
 // Internal wrapper struct
 // =============================================================================
 
+namespace {
+
+using Clock = std::chrono::steady_clock;
+
+uint64_t ElapsedNs(Clock::time_point start) {
+  return std::chrono::duration_cast<std::chrono::nanoseconds>(Clock::now() -
+                                                              start)
+      .count();
+}
+
+// Directives of a robots.txt as reported by the parser, in order, so they
+// can be fed to a matcher again without re-parsing the text.
+struct ParsedDirective {
+  enum Kind {
+    kUserAgent,
+    kAllow,
+    kDisallow,
+    kSitemap,
+    kCrawlDelay,
+    kRequestRate,
+    kContentSignal,
+    kUnknown,
+  };
+  Kind kind;
+  int line_num;
+  std::string value;   // Escaped pattern, agent, sitemap or unknown value.
+  std::string action;  // Key of an unknown directive.
+  double crawl_delay = 0;
+  googlebot::RequestRate request_rate;
+#if ROBOTS_SUPPORT_CONTENT_SIGNAL
+  googlebot::ContentSignal content_signal;
+#endif  // ROBOTS_SUPPORT_CONTENT_SIGNAL
+};
+
+class DirectiveRecorder : public googlebot::RobotsParseHandler {
+ public:
+  // In strict mode, directives accepted only through a typo variant of the
+  // key or without a colon are dropped.
+  explicit DirectiveRecorder(std::vector<ParsedDirective>* directives,
+                             bool strict = false)
+      : directives_(directives), strict_(strict) {}
+
+  void HandleRobotsStart() override {}
+  void HandleRobotsEnd() override {}
+  void HandleUserAgent(int line_num, std::string_view value) override {
+    Add(ParsedDirective::kUserAgent, line_num, value);
+  }
+  void HandleAllow(int line_num, std::string_view value) override {
+    Add(ParsedDirective::kAllow, line_num, value);
+  }
+  void HandleDisallow(int line_num, std::string_view value) override {
+    Add(ParsedDirective::kDisallow, line_num, value);
+  }
+  void HandleSitemap(int line_num, std::string_view value) override {
+    Add(ParsedDirective::kSitemap, line_num, value);
+  }
+  void HandleCrawlDelay(int line_num, double value) override {
+    Add(ParsedDirective::kCrawlDelay, line_num, {}).crawl_delay = value;
+  }
+  void HandleRequestRate(int line_num,
+                         const googlebot::RequestRate& rate) override {
+    Add(ParsedDirective::kRequestRate, line_num, {}).request_rate = rate;
+  }
+#if ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleContentSignal(int line_num,
+                           const googlebot::ContentSignal& signal) override {
+    Add(ParsedDirective::kContentSignal, line_num, {}).content_signal = signal;
+  }
+#endif  // ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleUnknownAction(int line_num, std::string_view action,
+                           std::string_view value) override {
+    Add(ParsedDirective::kUnknown, line_num, value).action.assign(action);
+  }
+
+  // Reported after the line's Handle* call.
+  void ReportLineMetadata(int line_num,
+                          const LineMetadata& metadata) override {
+    if (strict_ && metadata.has_directive &&
+        (metadata.is_acceptable_typo || metadata.is_missing_colon_separator) &&
+        !directives_->empty() && directives_->back().line_num == line_num) {
+      directives_->pop_back();
+    }
+  }
+
+ private:
+  ParsedDirective& Add(ParsedDirective::Kind kind, int line_num,
+                       std::string_view value) {
+    ParsedDirective& directive = directives_->emplace_back();
+    directive.kind = kind;
+    directive.line_num = line_num;
+    directive.value.assign(value);
+    return directive;
+  }
+
+  std::vector<ParsedDirective>* directives_;
+  bool strict_;
+};
+
+// An Allow/Disallow rule seen while tracing a check.
+struct TracedRule {
+  ParsedDirective::Kind kind;
+  int line_num;
+  std::string pattern;
+  int priority;      // Matched pattern length, or -1 if it did not match.
+  bool specific;     // In a group naming the agent rather than '*'.
+  bool superseded = false;  // Dropped for a later, more specific agent.
+};
+
+// RobotsMatcher that also tracks the Allow/Disallow rules of the groups that
+// applied to the queried user-agents, and optionally times each decision.
+class TrackingRobotsMatcher : public googlebot::RobotsMatcher {
+ public:
+  // Same as AllowedByRobots(), but fills in timing() when profiling is on.
+  bool Check(std::string_view robots_body,
+             const std::vector<std::string>* user_agents,
+             const std::string& url) {
+    if (strict_) {
+      std::vector<ParsedDirective> directives;
+      DirectiveRecorder recorder(&directives, /*strict=*/true);
+      googlebot::ParseRobotsTxt(robots_body, &recorder);
+      return CheckParsed(directives, user_agents, url);
+    }
+    if (!profiling_) return AllowedByRobots(robots_body, user_agents, url);
+
+    timing_ = robots_timing_t();
+    const Clock::time_point start = Clock::now();
+    std::string path = googlebot::GetPathParamsQuery(url);
+    timing_.url_parse_ns = ElapsedNs(start);
+    InitUserAgentsAndPath(user_agents, path.c_str());
+    ParseRobotsTxt(robots_body, this);
+    const bool allowed = !disallow();
+    timing_.total_ns = ElapsedNs(start);
+    return allowed;
+  }
+
+  // Same as Check(), but replays directives recorded by DirectiveRecorder
+  // instead of parsing robots.txt.
+  bool CheckParsed(const std::vector<ParsedDirective>& directives,
+                   const std::vector<std::string>* user_agents,
+                   const std::string& url) {
+    if (profiling_) timing_ = robots_timing_t();
+    const Clock::time_point start = profiling_ ? Clock::now() : Clock::time_point();
+    std::string path = googlebot::GetPathParamsQuery(url);
+    if (profiling_) timing_.url_parse_ns = ElapsedNs(start);
+    InitUserAgentsAndPath(user_agents, path.c_str());
+    HandleRobotsStart();
+    for (const ParsedDirective& d : directives) {
+      switch (d.kind) {
+        case ParsedDirective::kUserAgent:
+          HandleUserAgent(d.line_num, d.value);
+          break;
+        case ParsedDirective::kAllow:
+          HandleAllow(d.line_num, d.value);
+          break;
+        case ParsedDirective::kDisallow:
+          HandleDisallow(d.line_num, d.value);
+          break;
+        case ParsedDirective::kSitemap:
+          HandleSitemap(d.line_num, d.value);
+          break;
+        case ParsedDirective::kCrawlDelay:
+          HandleCrawlDelay(d.line_num, d.crawl_delay);
+          break;
+        case ParsedDirective::kRequestRate:
+          HandleRequestRate(d.line_num, d.request_rate);
+          break;
+        case ParsedDirective::kContentSignal:
+#if ROBOTS_SUPPORT_CONTENT_SIGNAL
+          HandleContentSignal(d.line_num, d.content_signal);
+#endif  // ROBOTS_SUPPORT_CONTENT_SIGNAL
+          break;
+        case ParsedDirective::kUnknown:
+          HandleUnknownAction(d.line_num, d.action, d.value);
+          break;
+      }
+    }
+    HandleRobotsEnd();
+    const bool allowed = !disallow();
+    if (profiling_) timing_.total_ns = ElapsedNs(start);
+    return allowed;
+  }
+
+  void set_profiling(bool enabled) { profiling_ = enabled; }
+  bool profiling() const { return profiling_; }
+  const robots_timing_t& timing() const { return timing_; }
+
+  // Returns the number of rules in the groups used for the last decision.
+  size_t effective_rule_count() const { return EffectiveRules().count; }
+
+  // Returns the total pattern length of those rules, in bytes.
+  size_t effective_rule_bytes() const { return EffectiveRules().bytes; }
+
+  void set_strict(bool enabled) { strict_ = enabled; }
+
+  void set_tracing(bool enabled) { tracing_ = enabled; }
+  bool tracing() const { return tracing_; }
+  // Allow/Disallow rules of the groups that applied to the queried
+  // user-agents during the last check, when tracing is on.
+  const std::vector<TracedRule>& traced_rules() const { return traced_rules_; }
+
+  // Returns the rule at matching_line(), or nullptr if no rule matched.
+  const ParsedDirective* matching_rule() const {
+    const int line = matching_line();
+    if (line == 0) return nullptr;
+    for (const ParsedDirective& rule : matched_rules_) {
+      if (rule.line_num == line) return &rule;
+    }
+    return nullptr;
+  }
+
+ protected:
+  void HandleRobotsStart() override {
+    RobotsMatcher::HandleRobotsStart();
+    global_rules_ = RuleStats();
+    specific_rules_ = RuleStats();
+    matched_rules_.clear();
+    traced_rules_.clear();
+  }
+
+  void HandleUserAgent(int line_num, std::string_view user_agent) override {
+    const Clock::time_point start = profiling_ ? Clock::now() : Clock::time_point();
+    const size_t previous_length = best_specific_agent_length_;
+    RobotsMatcher::HandleUserAgent(line_num, user_agent);
+    // A more specific agent discards the rules collected so far.
+    if (best_specific_agent_length_ > previous_length) {
+      specific_rules_ = RuleStats();
+      for (TracedRule& rule : traced_rules_) {
+        if (rule.specific) rule.superseded = true;
+      }
+    }
+    if (profiling_) timing_.group_selection_ns += ElapsedNs(start);
+  }
+
+  void HandleAllow(int line_num, std::string_view value) override {
+    // RobotsMatcher::HandleAllow() recurses for index.html patterns; only
+    // count and time the rule once.
+    const bool nested = std::exchange(in_allow_, true);
+    const Clock::time_point start = profiling_ ? Clock::now() : Clock::time_point();
+    if (!nested) CountRule(value);
+    if (tracing_ && !nested && seen_any_agent()) {
+      const int priority = TracePriority(
+          allow_, [&] { RobotsMatcher::HandleAllow(line_num, value); });
+      Trace(ParsedDirective::kAllow, line_num, value, priority);
+    } else {
+      RobotsMatcher::HandleAllow(line_num, value);
+    }
+    if (!nested) RecordMatch(allow_, ParsedDirective::kAllow, line_num, value);
+    if (profiling_ && !nested) timing_.rule_matching_ns += ElapsedNs(start);
+    in_allow_ = nested;
+  }
+
+  void HandleDisallow(int line_num, std::string_view value) override {
+    const Clock::time_point start = profiling_ ? Clock::now() : Clock::time_point();
+    CountRule(value);
+    if (tracing_ && seen_any_agent()) {
+      const int priority = TracePriority(
+          disallow_, [&] { RobotsMatcher::HandleDisallow(line_num, value); });
+      Trace(ParsedDirective::kDisallow, line_num, value, priority);
+    } else {
+      RobotsMatcher::HandleDisallow(line_num, value);
+    }
+    RecordMatch(disallow_, ParsedDirective::kDisallow, line_num, value);
+    if (profiling_) timing_.rule_matching_ns += ElapsedNs(start);
+  }
+
+ private:
+  struct RuleStats {
+    size_t count = 0;
+    size_t bytes = 0;
+  };
+
+  const RuleStats& EffectiveRules() const {
+    return ever_seen_specific_agent() ? specific_rules_ : global_rules_;
+  }
+
+  void CountRule(std::string_view value) {
+    if (!seen_any_agent()) return;
+    RuleStats& rules = seen_specific_agent_ ? specific_rules_ : global_rules_;
+    ++rules.count;
+    rules.bytes += value.size();
+  }
+
+  // Runs handle() against cleared matches to learn the match priority of
+  // the rule alone, then merges the result back the way RobotsMatcher
+  // would have: a rule only replaces a strictly lower priority.
+  template <typename Handle>
+  int TracePriority(MatchHierarchy& matches, Handle handle) {
+    const MatchHierarchy saved = matches;
+    matches.Clear();
+    handle();
+    const int priority =
+        std::max(matches.specific.priority(), matches.global.priority());
+    if (matches.specific.priority() <= saved.specific.priority()) {
+      matches.specific = saved.specific;
+    }
+    if (matches.global.priority() <= saved.global.priority()) {
+      matches.global = saved.global;
+    }
+    return priority;
+  }
+
+  void Trace(ParsedDirective::Kind kind, int line_num, std::string_view value,
+             int priority) {
+    TracedRule rule;
+    rule.kind = kind;
+    rule.line_num = line_num;
+    rule.pattern.assign(value);
+    rule.priority = priority;
+    rule.specific = seen_specific_agent_;
+    traced_rules_.push_back(std::move(rule));
+  }
+
+  // Keeps the pattern of a rule that just became the best match of its
+  // kind, so matching_rule() can report it. Only rules that raise a match
+  // priority are kept, a handful per check.
+  void RecordMatch(const MatchHierarchy& matches, ParsedDirective::Kind kind,
+                   int line_num, std::string_view value) {
+    if (matches.specific.line() != line_num &&
+        matches.global.line() != line_num) {
+      return;
+    }
+    ParsedDirective rule;
+    rule.kind = kind;
+    rule.line_num = line_num;
+    rule.value.assign(value);
+    matched_rules_.push_back(std::move(rule));
+  }
+
+  RuleStats global_rules_;
+  RuleStats specific_rules_;
+  std::vector<ParsedDirective> matched_rules_;
+  std::vector<TracedRule> traced_rules_;
+  bool in_allow_ = false;
+  bool tracing_ = false;
+  bool strict_ = false;
+  bool profiling_ = false;
+  robots_timing_t timing_ = robots_timing_t();
+};
+
+}  // namespace
+
 struct robots_matcher_s {
-  googlebot::RobotsMatcher matcher;
+  TrackingRobotsMatcher matcher;
+  // Agents of the last single-agent check, reused while the agent is
+  // unchanged to avoid an allocation per call.
+  std::vector<std::string> agents;
+
+  const std::vector<std::string>* SingleAgent(std::string_view agent) {
+    if (agents.size() != 1) {
+      agents.assign(1, std::string(agent));
+    } else if (agents[0] != agent) {
+      agents[0].assign(agent);
+    }
+    return &agents;
+  }
+};
+
+struct robots_txt_s {
+  std::vector<ParsedDirective> directives;
+  std::vector<size_t> sitemaps;  // Indices of kSitemap directives.
 };
 
 // =============================================================================
@@ -65,10 +435,12 @@ extern "C" bool robots_allowed_by_robots(
   }
 
   std::string_view robots_body(robots_txt, robots_txt_len);
-  std::string agent(user_agent, user_agent_len);
   std::string target_url(url, url_len);
 
-  return matcher->matcher.OneAgentAllowedByRobots(robots_body, agent, target_url);
+  return matcher->matcher.Check(
+      robots_body,
+      matcher->SingleAgent(std::string_view(user_agent, user_agent_len)),
+      target_url);
 }
 
 extern "C" bool robots_allowed_by_robots_multi(
@@ -77,7 +449,7 @@ extern "C" bool robots_allowed_by_robots_multi(
     const char* const* user_agents, const size_t* user_agent_lens,
     size_t num_user_agents,
     const char* url, size_t url_len) {
-  if (!matcher || !robots_txt || !user_agents || !user_agent_lens || !url) {
+  if (!matcher || !robots_txt || !user_agents || !url) {
     return true;  // Allow on invalid input
   }
 
@@ -85,11 +457,126 @@ extern "C" bool robots_allowed_by_robots_multi(
   std::vector<std::string> agents;
   agents.reserve(num_user_agents);
   for (size_t i = 0; i < num_user_agents; ++i) {
-    agents.emplace_back(user_agents[i], user_agent_lens[i]);
+    // Without lengths the strings are NUL-terminated (robots_allowed_multi).
+    if (user_agent_lens) {
+      agents.emplace_back(user_agents[i], user_agent_lens[i]);
+    } else {
+      agents.emplace_back(user_agents[i]);
+    }
   }
   std::string target_url(url, url_len);
 
-  return matcher->matcher.AllowedByRobots(robots_body, &agents, target_url);
+  return matcher->matcher.Check(robots_body, &agents, target_url);
+}
+
+// =============================================================================
+// Pre-parsed robots.txt
+// =============================================================================
+
+static robots_txt_t* ParseRobots(const char* robots_txt,
+                                 size_t robots_txt_len, bool strict) {
+  if (!robots_txt) return nullptr;
+  try {
+    auto robots = std::make_unique<robots_txt_t>();
+    DirectiveRecorder recorder(&robots->directives, strict);
+    googlebot::ParseRobotsTxt(std::string_view(robots_txt, robots_txt_len),
+                              &recorder);
+    for (size_t i = 0; i < robots->directives.size(); ++i) {
+      if (robots->directives[i].kind == ParsedDirective::kSitemap) {
+        robots->sitemaps.push_back(i);
+      }
+    }
+    return robots.release();
+  } catch (...) {
+    return nullptr;
+  }
+}
+
+extern "C" robots_txt_t* robots_txt_parse(const char* robots_txt,
+                                          size_t robots_txt_len) {
+  return ParseRobots(robots_txt, robots_txt_len, /*strict=*/false);
+}
+
+extern "C" robots_txt_t* robots_txt_parse_strict(const char* robots_txt,
+                                                 size_t robots_txt_len) {
+  return ParseRobots(robots_txt, robots_txt_len, /*strict=*/true);
+}
+
+extern "C" void robots_txt_free(robots_txt_t* robots) { delete robots; }
+
+extern "C" size_t robots_txt_sitemap_count(const robots_txt_t* robots) {
+  if (!robots) return 0;
+  return robots->sitemaps.size();
+}
+
+extern "C" const char* robots_txt_sitemap(const robots_txt_t* robots,
+                                          size_t index, size_t* len) {
+  if (!robots || index >= robots->sitemaps.size()) return nullptr;
+  const std::string& value = robots->directives[robots->sitemaps[index]].value;
+  if (len) *len = value.size();
+  return value.c_str();
+}
+
+extern "C" size_t robots_txt_directive_count(const robots_txt_t* robots) {
+  if (!robots) return 0;
+  return robots->directives.size();
+}
+
+extern "C" bool robots_txt_get_directive(const robots_txt_t* robots,
+                                         size_t index,
+                                         robots_directive_t* directive) {
+  if (!robots || !directive || index >= robots->directives.size()) {
+    return false;
+  }
+  const ParsedDirective& d = robots->directives[index];
+  switch (d.kind) {
+    case ParsedDirective::kUserAgent:
+      directive->kind = ROBOTS_DIRECTIVE_USER_AGENT;
+      break;
+    case ParsedDirective::kAllow:
+      directive->kind = ROBOTS_DIRECTIVE_ALLOW;
+      break;
+    case ParsedDirective::kDisallow:
+      directive->kind = ROBOTS_DIRECTIVE_DISALLOW;
+      break;
+    case ParsedDirective::kSitemap:
+      directive->kind = ROBOTS_DIRECTIVE_SITEMAP;
+      break;
+    case ParsedDirective::kCrawlDelay:
+      directive->kind = ROBOTS_DIRECTIVE_CRAWL_DELAY;
+      break;
+    case ParsedDirective::kRequestRate:
+      directive->kind = ROBOTS_DIRECTIVE_REQUEST_RATE;
+      break;
+    case ParsedDirective::kContentSignal:
+      directive->kind = ROBOTS_DIRECTIVE_CONTENT_SIGNAL;
+      break;
+    case ParsedDirective::kUnknown:
+      directive->kind = ROBOTS_DIRECTIVE_UNKNOWN;
+      break;
+  }
+  directive->line = d.line_num;
+  directive->value = d.value.data();
+  directive->value_len = d.value.size();
+  directive->key = d.action.data();
+  directive->key_len = d.action.size();
+  return true;
+}
+
+extern "C" bool robots_allowed_by_parsed(
+    robots_matcher_t* matcher,
+    const robots_txt_t* robots,
+    const char* user_agent, size_t user_agent_len,
+    const char* url, size_t url_len) {
+  if (!matcher || !robots || !user_agent || !url) {
+    return true;  // Allow on invalid input
+  }
+
+  std::string target_url(url, url_len);
+  return matcher->matcher.CheckParsed(
+      robots->directives,
+      matcher->SingleAgent(std::string_view(user_agent, user_agent_len)),
+      target_url);
 }
 
 // =============================================================================
@@ -101,11 +588,93 @@ extern "C" int robots_matching_line(const robots_matcher_t* matcher) {
   return matcher->matcher.matching_line();
 }
 
+extern "C" bool robots_matching_rule(const robots_matcher_t* matcher,
+                                     int* kind, const char** pattern,
+                                     size_t* pattern_len) {
+  if (!matcher) return false;
+  const ParsedDirective* rule = matcher->matcher.matching_rule();
+  if (!rule) return false;
+  if (kind) *kind = rule->kind == ParsedDirective::kAllow
+                        ? ROBOTS_DIRECTIVE_ALLOW
+                        : ROBOTS_DIRECTIVE_DISALLOW;
+  if (pattern) *pattern = rule->value.data();
+  if (pattern_len) *pattern_len = rule->value.size();
+  return true;
+}
+
 extern "C" bool robots_ever_seen_specific_agent(const robots_matcher_t* matcher) {
   if (!matcher) return false;
   return matcher->matcher.ever_seen_specific_agent();
 }
 
+extern "C" size_t robots_effective_rule_count(const robots_matcher_t* matcher) {
+  if (!matcher) return 0;
+  return matcher->matcher.effective_rule_count();
+}
+
+extern "C" size_t robots_effective_rule_bytes(const robots_matcher_t* matcher) {
+  if (!matcher) return 0;
+  return matcher->matcher.effective_rule_bytes();
+}
+
+// =============================================================================
+// Profiling
+// =============================================================================
+
+extern "C" void robots_set_profiling(robots_matcher_t* matcher, bool enabled) {
+  if (!matcher) return;
+  matcher->matcher.set_profiling(enabled);
+}
+
+extern "C" bool robots_get_timing(const robots_matcher_t* matcher,
+                                  robots_timing_t* timing) {
+  if (!matcher || !timing || !matcher->matcher.profiling()) return false;
+  *timing = matcher->matcher.timing();
+  return true;
+}
+
+// =============================================================================
+// Strict parsing
+// =============================================================================
+
+extern "C" void robots_set_strict(robots_matcher_t* matcher, bool enabled) {
+  if (!matcher) return;
+  matcher->matcher.set_strict(enabled);
+}
+
+// =============================================================================
+// Tracing
+// =============================================================================
+
+extern "C" void robots_set_tracing(robots_matcher_t* matcher, bool enabled) {
+  if (!matcher) return;
+  matcher->matcher.set_tracing(enabled);
+}
+
+extern "C" size_t robots_trace_rule_count(const robots_matcher_t* matcher) {
+  if (!matcher || !matcher->matcher.tracing()) return 0;
+  return matcher->matcher.traced_rules().size();
+}
+
+extern "C" bool robots_get_trace_rule(const robots_matcher_t* matcher,
+                                      size_t index,
+                                      robots_trace_rule_t* rule) {
+  if (!matcher || !rule || !matcher->matcher.tracing()) return false;
+  const std::vector<TracedRule>& rules = matcher->matcher.traced_rules();
+  if (index >= rules.size()) return false;
+  const TracedRule& traced = rules[index];
+  rule->kind = traced.kind == ParsedDirective::kAllow
+                   ? ROBOTS_DIRECTIVE_ALLOW
+                   : ROBOTS_DIRECTIVE_DISALLOW;
+  rule->line = traced.line_num;
+  rule->pattern = traced.pattern.data();
+  rule->pattern_len = traced.pattern.size();
+  rule->priority = traced.priority;
+  rule->specific = traced.specific;
+  rule->superseded = traced.superseded;
+  return true;
+}
+
 // =============================================================================
 // Crawl-delay support
 // =============================================================================
@@ -222,14 +791,281 @@ extern "C" bool robots_allows_search(const robots_matcher_t* matcher) {
 #endif
 }
 
+// =============================================================================
+// Parse callbacks
+// =============================================================================
+
+namespace {
+
+// Forwards parser events to the C callbacks that are set.
+class CallbackHandler : public googlebot::RobotsParseHandler {
+ public:
+  CallbackHandler(const robots_parse_callbacks_t* callbacks, void* context)
+      : cb_(*callbacks), ctx_(context) {}
+
+  void HandleRobotsStart() override {
+    if (cb_.robots_start) cb_.robots_start(ctx_);
+  }
+  void HandleRobotsEnd() override {
+    if (cb_.robots_end) cb_.robots_end(ctx_);
+  }
+  void HandleUserAgent(int line_num, std::string_view value) override {
+    if (cb_.user_agent) cb_.user_agent(ctx_, line_num, value.data(), value.size());
+  }
+  void HandleAllow(int line_num, std::string_view value) override {
+    if (cb_.allow) cb_.allow(ctx_, line_num, value.data(), value.size());
+  }
+  void HandleDisallow(int line_num, std::string_view value) override {
+    if (cb_.disallow) cb_.disallow(ctx_, line_num, value.data(), value.size());
+  }
+  void HandleSitemap(int line_num, std::string_view value) override {
+    if (cb_.sitemap) cb_.sitemap(ctx_, line_num, value.data(), value.size());
+  }
+  void HandleCrawlDelay(int line_num, double value) override {
+    if (cb_.crawl_delay) cb_.crawl_delay(ctx_, line_num, value);
+  }
+  void HandleRequestRate(int line_num,
+                         const googlebot::RequestRate& rate) override {
+    if (!cb_.request_rate) return;
+    robots_request_rate_t c_rate = {rate.requests, rate.seconds};
+    cb_.request_rate(ctx_, line_num, &c_rate);
+  }
+#if ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleContentSignal(int line_num,
+                           const googlebot::ContentSignal& signal) override {
+    if (!cb_.content_signal) return;
+    robots_content_signal_t c_signal = {TriState(signal.ai_train),
+                                        TriState(signal.ai_input),
+                                        TriState(signal.search)};
+    cb_.content_signal(ctx_, line_num, &c_signal);
+  }
+#endif  // ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleUnknownAction(int line_num, std::string_view action,
+                           std::string_view value) override {
+    if (cb_.unknown_action) {
+      cb_.unknown_action(ctx_, line_num, action.data(), action.size(),
+                         value.data(), value.size());
+    }
+  }
+
+ private:
+  static int8_t TriState(const std::optional<bool>& value) {
+    return value.has_value() ? (*value ? 1 : 0) : -1;
+  }
+
+  const robots_parse_callbacks_t cb_;
+  void* const ctx_;
+};
+
+}  // namespace
+
+extern "C" bool robots_parse(const char* robots_txt, size_t robots_txt_len,
+                             const robots_parse_callbacks_t* callbacks,
+                             void* context) {
+  if (!robots_txt || !callbacks) return false;
+  CallbackHandler handler(callbacks, context);
+  googlebot::ParseRobotsTxt(std::string_view(robots_txt, robots_txt_len),
+                            &handler);
+  return true;
+}
+
+namespace {
+
+// Parse handler that only forwards line metadata.
+class LineHandler : public googlebot::RobotsParseHandler {
+ public:
+  using Callback = void (*)(void*, int, const robots_line_metadata_t*);
+
+  LineHandler(Callback callback, void* context)
+      : callback_(callback), context_(context) {}
+
+  void HandleRobotsStart() override {}
+  void HandleRobotsEnd() override {}
+  void HandleUserAgent(int, std::string_view) override {}
+  void HandleAllow(int, std::string_view) override {}
+  void HandleDisallow(int, std::string_view) override {}
+  void HandleSitemap(int, std::string_view) override {}
+  void HandleCrawlDelay(int, double) override {}
+  void HandleRequestRate(int, const googlebot::RequestRate&) override {}
+#if ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleContentSignal(int, const googlebot::ContentSignal&) override {}
+#endif  // ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleUnknownAction(int, std::string_view, std::string_view) override {}
+
+  void ReportLineMetadata(int line_num, const LineMetadata& metadata) override {
+    robots_line_metadata_t line;
+    line.is_empty = metadata.is_empty;
+    line.has_comment = metadata.has_comment;
+    line.is_comment = metadata.is_comment;
+    line.has_directive = metadata.has_directive;
+    line.is_acceptable_typo = metadata.is_acceptable_typo;
+    line.is_line_too_long = metadata.is_line_too_long;
+    line.is_missing_colon_separator = metadata.is_missing_colon_separator;
+    callback_(context_, line_num, &line);
+  }
+
+ private:
+  Callback callback_;
+  void* context_;
+};
+
+}  // namespace
+
+extern "C" bool robots_parse_lines(
+    const char* robots_txt, size_t robots_txt_len,
+    void (*callback)(void* context, int line,
+                     const robots_line_metadata_t* metadata),
+    void* context) {
+  if (!robots_txt || !callback) return false;
+  LineHandler handler(callback, context);
+  googlebot::ParseRobotsTxt(std::string_view(robots_txt, robots_txt_len),
+                            &handler);
+  return true;
+}
+
+// =============================================================================
+// Parse statistics
+// =============================================================================
+
+namespace {
+
+// Parse handler that only counts lines; it never matches anything.
+class ParseStatsCollector : public googlebot::RobotsParseHandler {
+ public:
+  explicit ParseStatsCollector(robots_parse_stats_t* stats) : stats_(stats) {}
+
+  void HandleRobotsStart() override {
+    *stats_ = robots_parse_stats_t();
+    seen_agent_ = false;
+  }
+  void HandleRobotsEnd() override {}
+
+  void HandleUserAgent(int, std::string_view user_agent) override {
+    seen_agent_ = true;
+    // Agents are matched on a leading [a-zA-Z_-]+ token; values such as
+    // "\xF0\x9F\xA4\x96bot" yield an empty token and match no crawler.
+    bool global = !user_agent.empty() && user_agent[0] == '*';
+    bool has_token =
+        !user_agent.empty() &&
+        (std::isalpha(static_cast<unsigned char>(user_agent[0])) ||
+         user_agent[0] == '-' || user_agent[0] == '_');
+    if (!global && !has_token) ++stats_->unmatchable_user_agents;
+  }
+  void HandleAllow(int, std::string_view) override { CountGroupRule(); }
+  void HandleDisallow(int, std::string_view) override { CountGroupRule(); }
+  void HandleSitemap(int, std::string_view) override {}
+  void HandleCrawlDelay(int, double) override { CountGroupRule(); }
+  void HandleRequestRate(int, const googlebot::RequestRate&) override {
+    CountGroupRule();
+  }
+#if ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleContentSignal(int, const googlebot::ContentSignal&) override {
+    CountGroupRule();
+  }
+#endif  // ROBOTS_SUPPORT_CONTENT_SIGNAL
+  void HandleUnknownAction(int, std::string_view, std::string_view) override {
+    ++stats_->unknown_directives;
+  }
+
+  void ReportLineMetadata(int, const LineMetadata& metadata) override {
+    ++stats_->total_lines;
+    if (metadata.has_directive) {
+      ++stats_->directives;
+    } else if (!metadata.is_empty && !metadata.is_comment) {
+      ++stats_->malformed_lines;
+    }
+    if (metadata.is_acceptable_typo) ++stats_->typo_directives;
+    if (metadata.is_line_too_long) ++stats_->truncated_lines;
+  }
+
+ private:
+  void CountGroupRule() {
+    if (!seen_agent_) ++stats_->rules_outside_groups;
+  }
+
+  robots_parse_stats_t* stats_;
+  bool seen_agent_ = false;
+};
+
+// Decodes the UTF-8 sequence at s[*i], advancing *i past it. Returns -1 and
+// advances by one byte for invalid sequences.
+int32_t DecodeUtf8(std::string_view s, size_t* i) {
+  auto byte = [&](size_t k) { return static_cast<unsigned char>(s[k]); };
+  unsigned char lead = byte(*i);
+  int len = lead < 0x80 ? 1 : lead < 0xC2 ? 0 : lead < 0xE0 ? 2
+          : lead < 0xF0 ? 3 : lead < 0xF5 ? 4 : 0;
+  if (len == 0 || *i + len > s.size()) {
+    ++*i;
+    return -1;
+  }
+  int32_t cp = len == 1 ? lead : lead & (0x3F >> (len - 1));
+  for (int k = 1; k < len; ++k) {
+    if ((byte(*i + k) & 0xC0) != 0x80) {
+      ++*i;
+      return -1;
+    }
+    cp = (cp << 6) | (byte(*i + k) & 0x3F);
+  }
+  static constexpr int32_t kMin[] = {0, 0, 0x80, 0x800, 0x10000};
+  if (cp < kMin[len] || cp > 0x10FFFF || (cp >= 0xD800 && cp <= 0xDFFF)) {
+    ++*i;
+    return -1;
+  }
+  *i += len;
+  return cp;
+}
+
+// Zero-width and bidirectional formatting characters: invisible in editors,
+// but part of the pattern, so a rule containing one silently never matches.
+bool IsInvisibleFormatChar(int32_t cp) {
+  return (cp >= 0x200B && cp <= 0x200F) || (cp >= 0x202A && cp <= 0x202E) ||
+         cp == 0x2060 || (cp >= 0x2066 && cp <= 0x2069) || cp == 0xFEFF;
+}
+
+void CountEncodingIssues(std::string_view robots_txt,
+                         robots_parse_stats_t* stats) {
+  size_t i = 0;
+  // A leading byte order mark is skipped by the parser.
+  if (robots_txt.substr(0, 3) == "\xEF\xBB\xBF") i = 3;
+  bool invalid = false;
+  bool invisible = false;
+  while (i <= robots_txt.size()) {
+    if (i == robots_txt.size() || robots_txt[i] == '\n' ||
+        robots_txt[i] == '\r') {
+      if (invalid) ++stats->invalid_utf8_lines;
+      if (invisible) ++stats->invisible_char_lines;
+      invalid = invisible = false;
+      ++i;
+      continue;
+    }
+    int32_t cp = DecodeUtf8(robots_txt, &i);
+    if (cp < 0 || cp == 0xFFFD) invalid = true;
+    if (IsInvisibleFormatChar(cp)) invisible = true;
+  }
+}
+
+}  // namespace
+
+extern "C" bool robots_parse_stats(const char* robots_txt, size_t robots_txt_len,
+                                   robots_parse_stats_t* stats) {
+  if (!robots_txt || !stats) return false;
+  std::string_view body(robots_txt, robots_txt_len);
+  ParseStatsCollector collector(stats);
+  googlebot::ParseRobotsTxt(body, &collector);
+  CountEncodingIssues(body, stats);
+  return true;
+}
+
 // =============================================================================
 // Utility functions
 // =============================================================================
 
 extern "C" bool robots_is_valid_user_agent(const char* user_agent, size_t len) {
   if (!user_agent || len == 0) return false;
-  return googlebot::RobotsMatcher::IsValidUserAgentToObey(
-      std::string_view(user_agent, len));
+  // IsValidUserAgentToObey() scans up to a terminating NUL, which the
+  // caller's buffer need not have.
+  const std::string agent(user_agent, len);
+  return googlebot::RobotsMatcher::IsValidUserAgentToObey(agent);
 }
 
 extern "C" const char* robots_version(void) {
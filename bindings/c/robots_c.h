@@ -65,6 +65,10 @@ extern "C" {
 // Opaque pointer to RobotsMatcher instance.
 typedef struct robots_matcher_s robots_matcher_t;
 
+// Opaque pointer to a parsed robots.txt. Immutable once created, so it may
+// be shared between threads and matchers.
+typedef struct robots_txt_s robots_txt_t;
+
 // Request-rate value (requests per time period).
 typedef struct {
   int requests;  // Number of requests allowed
@@ -79,6 +83,98 @@ typedef struct {
   int8_t search;    // search: Building search indexes and providing results
 } robots_content_signal_t;
 
+// Kind of a directive in a parsed robots.txt.
+typedef enum {
+  ROBOTS_DIRECTIVE_USER_AGENT = 0,
+  ROBOTS_DIRECTIVE_ALLOW = 1,
+  ROBOTS_DIRECTIVE_DISALLOW = 2,
+  ROBOTS_DIRECTIVE_SITEMAP = 3,
+  ROBOTS_DIRECTIVE_CRAWL_DELAY = 4,
+  ROBOTS_DIRECTIVE_REQUEST_RATE = 5,
+  ROBOTS_DIRECTIVE_CONTENT_SIGNAL = 6,
+  ROBOTS_DIRECTIVE_UNKNOWN = 7,
+} robots_directive_kind_t;
+
+// A directive of a parsed robots.txt. The strings are owned by the parsed
+// robots.txt and are not NUL-terminated.
+typedef struct {
+  int kind;            // robots_directive_kind_t
+  int line;            // Line number in the robots.txt
+  const char* value;   // Agent, escaped pattern, sitemap or unknown value
+  size_t value_len;    // 0 for crawl-delay, request-rate and content-signal
+  const char* key;     // Key of an unknown directive, otherwise empty
+  size_t key_len;
+} robots_directive_t;
+
+// Time spent in each phase of the last check, in nanoseconds. Only filled in
+// when profiling is enabled with robots_set_profiling().
+typedef struct {
+  uint64_t url_parse_ns;        // Extracting the path from the URL
+  uint64_t group_selection_ns;  // Handling user-agent lines
+  uint64_t rule_matching_ns;    // Matching Allow/Disallow patterns
+  uint64_t total_ns;            // Whole check, including line tokenizing
+} robots_timing_t;
+
+// An Allow/Disallow rule considered during a traced check, see
+// robots_set_tracing(). The pattern is percent-escaped, not NUL-terminated,
+// and stays valid until the next check.
+typedef struct {
+  int kind;             // ROBOTS_DIRECTIVE_ALLOW or ROBOTS_DIRECTIVE_DISALLOW
+  int line;             // Line number in the robots.txt
+  const char* pattern;
+  size_t pattern_len;
+  int priority;         // Matched pattern length, or -1 if it did not match
+  bool specific;        // In a group naming the agent rather than '*'
+  bool superseded;      // In a group dropped for a more specific agent
+} robots_trace_rule_t;
+
+// Counters describing how the parser treated each line of a robots.txt.
+// Lines counted as unknown, malformed or outside a group do not affect
+// matching decisions.
+typedef struct {
+  int total_lines;           // Lines seen, including empty and comment lines
+  int directives;            // Lines with a key-value pair (known or unknown)
+  int unknown_directives;    // Directives with an unrecognized key
+  int malformed_lines;       // Non-empty, non-comment lines without a directive
+  int rules_outside_groups;  // Group rules found before any user-agent line
+  int typo_directives;       // Directives accepted through a typo variant
+  int truncated_lines;       // Lines cut off at the maximum line length
+  int invalid_utf8_lines;    // Lines with invalid UTF-8 or U+FFFD
+  int invisible_char_lines;  // Lines with zero-width or bidi control characters
+  int unmatchable_user_agents;  // User-agent lines no crawler name can match
+} robots_parse_stats_t;
+
+// How the parser treated one line of a robots.txt, see robots_parse_lines().
+typedef struct {
+  bool is_empty;          // Nothing but whitespace
+  bool has_comment;       // Has a '#' comment, possibly after a directive
+  bool is_comment;        // Nothing but a comment
+  bool has_directive;     // Reported to a handler as a directive
+  bool is_acceptable_typo;          // Key accepted as a typo, e.g. "Dissalow"
+  bool is_line_too_long;            // Cut off at the maximum line length
+  bool is_missing_colon_separator;  // Key and value without a ':'
+} robots_line_metadata_t;
+
+// Callbacks for robots_parse(), one per parser event. Any of them may be
+// NULL. String arguments are not NUL-terminated and are only valid during
+// the call; Allow/Disallow patterns are already percent-escaped.
+typedef struct {
+  void (*robots_start)(void* context);
+  void (*robots_end)(void* context);
+  void (*user_agent)(void* context, int line, const char* value, size_t len);
+  void (*allow)(void* context, int line, const char* value, size_t len);
+  void (*disallow)(void* context, int line, const char* value, size_t len);
+  void (*sitemap)(void* context, int line, const char* value, size_t len);
+  void (*crawl_delay)(void* context, int line, double seconds);
+  void (*request_rate)(void* context, int line,
+                       const robots_request_rate_t* rate);
+  // Only called if Content-Signal support is compiled in.
+  void (*content_signal)(void* context, int line,
+                         const robots_content_signal_t* signal);
+  void (*unknown_action)(void* context, int line, const char* key,
+                         size_t key_len, const char* value, size_t value_len);
+} robots_parse_callbacks_t;
+
 // =============================================================================
 // Matcher lifecycle
 // =============================================================================
@@ -122,7 +218,8 @@ ROBOTS_API bool robots_allowed_by_robots(
 //   robots_txt:       robots.txt content
 //   robots_txt_len:   length of robots_txt
 //   user_agents:      array of user-agent strings
-//   user_agent_lens:  array of user-agent string lengths
+//   user_agent_lens:  array of user-agent string lengths, or NULL if the
+//                     user-agents are NUL-terminated
 //   num_user_agents:  number of user-agents
 //   url:              URL to check (must be %-encoded per RFC3986)
 //   url_len:          length of url
@@ -135,6 +232,52 @@ ROBOTS_API bool robots_allowed_by_robots_multi(
     size_t num_user_agents,
     const char* url, size_t url_len);
 
+// =============================================================================
+// Pre-parsed robots.txt
+// =============================================================================
+
+// Parses robots.txt once for repeated checks with robots_allowed_by_parsed().
+// Returns NULL if robots_txt is NULL or on allocation failure.
+// Caller must free with robots_txt_free().
+ROBOTS_API robots_txt_t* robots_txt_parse(const char* robots_txt,
+                                          size_t robots_txt_len);
+
+// Same as robots_txt_parse(), but drops directives the parser accepts only
+// leniently: typo variants of a key (such as "Dissalow") and lines without
+// a colon (such as "Disallow /x").
+ROBOTS_API robots_txt_t* robots_txt_parse_strict(const char* robots_txt,
+                                                 size_t robots_txt_len);
+
+// Frees a parsed robots.txt. Safe to call with NULL.
+ROBOTS_API void robots_txt_free(robots_txt_t* robots);
+
+// Returns the number of Sitemap: lines in a parsed robots.txt.
+ROBOTS_API size_t robots_txt_sitemap_count(const robots_txt_t* robots);
+
+// Returns the value of the index-th Sitemap: line, in file order, and stores
+// its length in *len if len is not NULL. The string is NUL-terminated and
+// owned by robots. Returns NULL if index is out of range.
+ROBOTS_API const char* robots_txt_sitemap(const robots_txt_t* robots,
+                                          size_t index, size_t* len);
+
+// Returns the number of directives in a parsed robots.txt. Lines without a
+// directive (empty, comment-only or malformed) are not included.
+ROBOTS_API size_t robots_txt_directive_count(const robots_txt_t* robots);
+
+// Gets the index-th directive, in file order. Returns false if index is out
+// of range or directive is NULL.
+ROBOTS_API bool robots_txt_get_directive(const robots_txt_t* robots,
+                                         size_t index,
+                                         robots_directive_t* directive);
+
+// Same as robots_allowed_by_robots(), but without parsing the robots.txt
+// again. The matcher state accessors below work the same way afterwards.
+ROBOTS_API bool robots_allowed_by_parsed(
+    robots_matcher_t* matcher,
+    const robots_txt_t* robots,
+    const char* user_agent, size_t user_agent_len,
+    const char* url, size_t url_len);
+
 // =============================================================================
 // Matcher state accessors (call after robots_allowed_by_robots)
 // =============================================================================
@@ -142,9 +285,66 @@ ROBOTS_API bool robots_allowed_by_robots_multi(
 // Returns the line number that matched, or 0 if no match.
 ROBOTS_API int robots_matching_line(const robots_matcher_t* matcher);
 
+// Gets the Allow or Disallow rule at robots_matching_line(): its kind
+// (ROBOTS_DIRECTIVE_ALLOW or ROBOTS_DIRECTIVE_DISALLOW) and its
+// percent-escaped pattern, which is not NUL-terminated and stays valid until
+// the next check. Any output may be NULL. Returns false if no rule matched.
+ROBOTS_API bool robots_matching_rule(const robots_matcher_t* matcher,
+                                     int* kind, const char** pattern,
+                                     size_t* pattern_len);
+
 // Returns true if a specific user-agent block was found (not just '*').
 ROBOTS_API bool robots_ever_seen_specific_agent(const robots_matcher_t* matcher);
 
+// Returns the number of Allow/Disallow rules in the group(s) that applied to
+// the checked user-agent: the specific group(s) if any, otherwise '*'.
+ROBOTS_API size_t robots_effective_rule_count(const robots_matcher_t* matcher);
+
+// Returns the combined pattern length, in bytes, of the rules counted by
+// robots_effective_rule_count().
+ROBOTS_API size_t robots_effective_rule_bytes(const robots_matcher_t* matcher);
+
+// =============================================================================
+// Profiling
+// =============================================================================
+
+// Enables or disables per-check timing. Disabled by default; when enabled,
+// every check pays for a few clock reads per robots.txt line.
+ROBOTS_API void robots_set_profiling(robots_matcher_t* matcher, bool enabled);
+
+// Gets the timing breakdown of the last check. Returns false if profiling
+// is disabled.
+ROBOTS_API bool robots_get_timing(const robots_matcher_t* matcher,
+                                  robots_timing_t* timing);
+
+// =============================================================================
+// Strict parsing
+// =============================================================================
+
+// Enables or disables strict parsing for checks against robots.txt text, as
+// in robots_txt_parse_strict(). Disabled by default.
+ROBOTS_API void robots_set_strict(robots_matcher_t* matcher, bool enabled);
+
+// =============================================================================
+// Tracing
+// =============================================================================
+
+// Enables or disables recording of the rules considered by each check.
+// Disabled by default; when enabled, every check copies the patterns of
+// the groups that apply.
+ROBOTS_API void robots_set_tracing(robots_matcher_t* matcher, bool enabled);
+
+// Returns the number of rules recorded by the last check, or 0 if tracing
+// is disabled. Rules are in file order; rules outside any group applying to
+// the agent are not recorded.
+ROBOTS_API size_t robots_trace_rule_count(const robots_matcher_t* matcher);
+
+// Gets the index-th recorded rule. Returns false if tracing is disabled or
+// index is out of range.
+ROBOTS_API bool robots_get_trace_rule(const robots_matcher_t* matcher,
+                                      size_t index,
+                                      robots_trace_rule_t* rule);
+
 // =============================================================================
 // Crawl-delay support (non-standard directive)
 // =============================================================================
@@ -189,6 +389,35 @@ ROBOTS_API bool robots_allows_ai_train(const robots_matcher_t* matcher);
 ROBOTS_API bool robots_allows_ai_input(const robots_matcher_t* matcher);
 ROBOTS_API bool robots_allows_search(const robots_matcher_t* matcher);
 
+// =============================================================================
+// Parse callbacks
+// =============================================================================
+
+// Parses robots.txt and calls the matching callback for each directive, in
+// file order, between robots_start and robots_end. context is passed to
+// every callback. Returns false if robots_txt or callbacks is NULL.
+ROBOTS_API bool robots_parse(const char* robots_txt, size_t robots_txt_len,
+                             const robots_parse_callbacks_t* callbacks,
+                             void* context);
+
+// Parses robots.txt and calls callback once per line, in order, with the
+// 1-based line number. Lines are split on "\n", "\r" and "\r\n" after a
+// leading byte order mark. Returns false if robots_txt or callback is NULL.
+ROBOTS_API bool robots_parse_lines(
+    const char* robots_txt, size_t robots_txt_len,
+    void (*callback)(void* context, int line,
+                     const robots_line_metadata_t* metadata),
+    void* context);
+
+// =============================================================================
+// Parse statistics
+// =============================================================================
+
+// Parses robots.txt and fills in counters for ignored or unusual lines.
+// Returns false if robots_txt or stats is NULL.
+ROBOTS_API bool robots_parse_stats(const char* robots_txt, size_t robots_txt_len,
+                                   robots_parse_stats_t* stats);
+
 // =============================================================================
 // Utility functions
 // =============================================================================
@@ -1,8 +1,40 @@
+use std::path::{Path, PathBuf};
+
 fn main() {
-    // Link to the robots library
-    println!("cargo:rustc-link-search=native=../../build");
-    println!("cargo:rustc-link-search=native=../../cmake-build");
-    println!("cargo:rustc-link-lib=dylib=robots");
+    println!("cargo:rustc-check-cfg=cfg(robots_static)");
+
+    // With `dlopen` the library is loaded at runtime, see src/dynamic.rs.
+    if std::env::var_os("CARGO_FEATURE_DLOPEN").is_some() {
+        return;
+    }
+
+    if std::env::var_os("CARGO_FEATURE_VENDORED").is_some() {
+        build_vendored();
+        println!("cargo:rustc-cfg=robots_static");
+        return;
+    }
+
+    let is_static = link_static();
+    if is_static {
+        println!("cargo:rustc-cfg=robots_static");
+    }
+
+    // Link to the robots library: ROBOTS_LIB_DIR, then a build in this
+    // repository, then an installed copy. The repository build comes first
+    // so that development does not pick up an older installed library.
+    println!("cargo:rerun-if-env-changed=ROBOTS_LIB_DIR");
+    let repo_dirs = [
+        PathBuf::from("../../build"),
+        PathBuf::from("../../cmake-build"),
+    ];
+    match std::env::var("ROBOTS_LIB_DIR") {
+        Ok(dir) => link_from(&[PathBuf::from(dir)], is_static),
+        Err(_) if repo_dirs.iter().any(|dir| has_library(dir, is_static)) => {
+            link_from(&repo_dirs, is_static)
+        }
+        Err(_) if !is_static && find_installed() => {}
+        Err(_) => link_from(&repo_dirs, is_static),
+    }
 
     // On macOS, also link to libc++
     #[cfg(target_os = "macos")]
@@ -12,3 +44,105 @@ fn main() {
     #[cfg(target_os = "linux")]
     println!("cargo:rustc-link-lib=dylib=stdc++");
 }
+
+fn link_from(lib_dirs: &[PathBuf], is_static: bool) {
+    for dir in lib_dirs {
+        println!("cargo:rustc-link-search=native={}", dir.display());
+    }
+    if !is_static {
+        println!("cargo:rustc-link-lib=dylib=robots");
+        return;
+    }
+    println!("cargo:rustc-link-lib=static=robots");
+    // The CMake build links ada into librobots; a static archive needs it
+    // passed separately.
+    for dir in lib_dirs {
+        let ada = dir.join("_deps/ada-build/src");
+        if ada.join("libada.a").exists() {
+            println!("cargo:rustc-link-search=native={}", ada.display());
+            println!("cargo:rustc-link-lib=static=ada");
+            break;
+        }
+    }
+}
+
+/// Whether `dir` holds a robots library of the requested kind.
+fn has_library(dir: &Path, is_static: bool) -> bool {
+    let names: &[&str] = if is_static {
+        &["librobots.a", "robots.lib"]
+    } else {
+        &[
+            "librobots.so",
+            "librobots.dylib",
+            "robots.dll",
+            "robots.lib",
+        ]
+    };
+    names.iter().any(|name| dir.join(name).exists())
+}
+
+/// Looks up an installed shared library with pkg-config (robots.pc,
+/// installed by `make install`), or vcpkg on Windows. Both emit the link
+/// flags. Not used for static linking: `make install` does not install
+/// ada, which `librobots.a` needs.
+fn find_installed() -> bool {
+    if pkg_config::Config::new().probe("robots").is_ok() {
+        return true;
+    }
+    #[cfg(windows)]
+    if vcpkg::find_package("robots").is_ok() {
+        return true;
+    }
+    false
+}
+
+/// Whether to link librobots.a rather than the shared library. A set
+/// `ROBOTS_STATIC` overrides the `static` and `dynamic` features.
+fn link_static() -> bool {
+    println!("cargo:rerun-if-env-changed=ROBOTS_STATIC");
+    if let Ok(value) = std::env::var("ROBOTS_STATIC") {
+        return !matches!(value.as_str(), "" | "0" | "false" | "no" | "off");
+    }
+    let is_static = std::env::var_os("CARGO_FEATURE_STATIC").is_some();
+    if is_static && std::env::var_os("CARGO_FEATURE_DYNAMIC").is_some() {
+        panic!("the `static` and `dynamic` features are mutually exclusive; set ROBOTS_STATIC to choose");
+    }
+    is_static
+}
+
+/// Compiles robots.cc and the C shim into a static library. Sources are
+/// taken from `ROBOTS_SOURCE_DIR`, or by default from `vendor/`, which
+/// links to the repository's sources and is copied into the published
+/// package. URLs are parsed without ada, as in the single-header build.
+fn build_vendored() {
+    println!("cargo:rerun-if-env-changed=ROBOTS_SOURCE_DIR");
+    let root = match std::env::var_os("ROBOTS_SOURCE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("vendor"),
+    };
+    let sources = [root.join("robots.cc"), root.join("bindings/c/robots_c.cc")];
+    for source in &sources {
+        if !source.exists() {
+            panic!(
+                "vendored build: {} not found; set ROBOTS_SOURCE_DIR to a robotstxt checkout",
+                source.display()
+            );
+        }
+        println!("cargo:rerun-if-changed={}", source.display());
+    }
+    println!("cargo:rerun-if-changed={}", root.join("robots.h").display());
+    println!(
+        "cargo:rerun-if-changed={}",
+        root.join("bindings/c/robots_c.h").display()
+    );
+
+    cc::Build::new()
+        .cpp(true)
+        .std("c++20")
+        .include(&root)
+        .include(root.join("bindings/c"))
+        .define("ROBOTS_SUPPORT_CONTENT_SIGNAL", "1")
+        .files(&sources)
+        .warnings(false)
+        .compile("robots");
+}
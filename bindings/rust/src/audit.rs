@@ -0,0 +1,554 @@
+//! Audit helpers that attribute matching decisions to robots.txt rules.
+//!
+//! Rules are identified by their line number in the robots.txt, the same
+//! number reported by [`RobotsMatcher::matching_line`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::audit::urls_blocked_by;
+//!
+//! let robots_txt = "User-agent: *\nDisallow: /search\nDisallow: /tmp/\n";
+//! let urls = ["https://example.com/search?q=a", "https://example.com/tmp/x", "https://example.com/"];
+//! let blocked = urls_blocked_by(robots_txt, "Googlebot", 2, urls);
+//! assert_eq!(blocked, ["https://example.com/search?q=a"]);
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::guard::MAJOR_CRAWLERS;
+use crate::{RobotsMatcher, RobotsTxt};
+
+/// Returns the URLs from `urls` that are disallowed for `user_agent` by the
+/// rule on line `line`, in input order.
+///
+/// A URL counts only if that rule decides the outcome: URLs that the rule
+/// matches but a longer Allow overrides, or that another Disallow matches
+/// more specifically, are not included.
+pub fn urls_blocked_by<'a, I>(
+    robots_txt: &str,
+    user_agent: &str,
+    line: i32,
+    urls: I,
+) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let matcher = RobotsMatcher::new();
+    urls.into_iter()
+        .filter(|url| {
+            !matcher.is_allowed(robots_txt, user_agent, url) && matcher.matching_line() == line
+        })
+        .collect()
+}
+
+/// Traffic decided by a single rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleImpact {
+    /// Line number of the rule.
+    pub line: i32,
+    /// True for an Allow rule, false for a Disallow rule.
+    pub allows: bool,
+    /// Number of log entries decided by the rule. A URL listed twice in
+    /// the log counts twice; aggregate the log by URL first to count URLs.
+    pub urls: usize,
+    /// Sum of their hit counts.
+    pub hits: u64,
+}
+
+/// Rules ranked by the traffic they decide, see [`rank_rules_by_traffic`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImpactReport {
+    /// Rules ordered by hits, highest first; ties keep line order.
+    pub rules: Vec<RuleImpact>,
+    /// Log entries no rule matched (allowed by default).
+    pub unmatched_urls: usize,
+    pub unmatched_hits: u64,
+}
+
+impl ImpactReport {
+    /// Renders the ranked rules as CSV with a `line,decision,urls,hits` header.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("line,decision,urls,hits\n");
+        for rule in &self.rules {
+            let decision = if rule.allows { "allow" } else { "disallow" };
+            let _ = writeln!(
+                csv,
+                "{},{},{},{}",
+                rule.line, decision, rule.urls, rule.hits
+            );
+        }
+        csv
+    }
+}
+
+/// Ranks the rules of `robots_txt` by the traffic they decide for
+/// `user_agent`, given `(url, hit_count)` pairs from an access log.
+pub fn rank_rules_by_traffic<'a, I>(robots_txt: &str, user_agent: &str, log: I) -> ImpactReport
+where
+    I: IntoIterator<Item = (&'a str, u64)>,
+{
+    let matcher = RobotsMatcher::new();
+    let mut report = ImpactReport::default();
+    let mut by_line: BTreeMap<i32, RuleImpact> = BTreeMap::new();
+    for (url, hits) in log {
+        let allows = matcher.is_allowed(robots_txt, user_agent, url);
+        let line = matcher.matching_line();
+        if line == 0 {
+            report.unmatched_urls += 1;
+            report.unmatched_hits += hits;
+            continue;
+        }
+        let impact = by_line.entry(line).or_insert(RuleImpact {
+            line,
+            allows,
+            urls: 0,
+            hits: 0,
+        });
+        impact.urls += 1;
+        impact.hits += hits;
+    }
+    report.rules = by_line.into_values().collect();
+    report.rules.sort_by_key(|r| Reverse(r.hits));
+    report
+}
+
+/// A sitemap URL that robots.txt disallows for a search crawler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapConflict {
+    /// The URL listed in the sitemap.
+    pub url: String,
+    /// The crawler it is disallowed for, one of [`MAJOR_CRAWLERS`].
+    pub user_agent: String,
+    /// Line number of the Disallow rule that blocks it.
+    pub line: i32,
+}
+
+/// Flags URLs from `sitemap_urls` that `robots_txt` disallows for any of
+/// the [`MAJOR_CRAWLERS`].
+///
+/// Listing a URL in a sitemap asks crawlers to fetch it, so a Disallow for
+/// the same URL is contradictory. One conflict is reported per URL and
+/// crawler, in sitemap order and then [`MAJOR_CRAWLERS`] order.
+pub fn audit_consistency<'a, I>(robots_txt: &str, sitemap_urls: I) -> Vec<SitemapConflict>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let matcher = RobotsMatcher::new();
+    let mut conflicts = Vec::new();
+    for url in sitemap_urls {
+        for agent in MAJOR_CRAWLERS {
+            if !matcher.is_allowed(robots_txt, agent, url) {
+                conflicts.push(SitemapConflict {
+                    url: url.to_string(),
+                    user_agent: (*agent).to_string(),
+                    line: matcher.matching_line(),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// A URL that two versions of a robots.txt decide differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub url: String,
+    /// Decision and matching line under the current robots.txt.
+    pub before: bool,
+    pub before_line: i32,
+    /// Decision and matching line under the candidate robots.txt.
+    pub after: bool,
+    pub after_line: i32,
+}
+
+/// Decisions that change between two robots.txt versions, see
+/// [`compare_policies`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DivergenceReport {
+    /// Number of URLs checked.
+    pub checked: usize,
+    /// URLs whose decision changes, in input order.
+    pub diverged: Vec<Divergence>,
+}
+
+impl DivergenceReport {
+    /// Returns the fraction of checked URLs whose decision changes, or 0
+    /// if none were checked.
+    pub fn divergence_rate(&self) -> f64 {
+        if self.checked == 0 {
+            0.0
+        } else {
+            self.diverged.len() as f64 / self.checked as f64
+        }
+    }
+}
+
+/// Checks `urls` against the current robots.txt `before` and a candidate
+/// `after` for `user_agent`, and reports the URLs whose decision changes.
+///
+/// Run it on a sample of recent checks before deploying a new robots.txt
+/// to see how much of the crawl it would turn away or let in.
+pub fn compare_policies<'a, I>(
+    before: &str,
+    after: &str,
+    user_agent: &str,
+    urls: I,
+) -> DivergenceReport
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let (before, after) = (RobotsTxt::parse(before), RobotsTxt::parse(after));
+    let matcher = RobotsMatcher::new();
+    let mut report = DivergenceReport::default();
+    for url in urls {
+        report.checked += 1;
+        let allowed_before = matcher.is_allowed_parsed(&before, user_agent, url);
+        let before_line = matcher.matching_line();
+        let allowed_after = matcher.is_allowed_parsed(&after, user_agent, url);
+        if allowed_before != allowed_after {
+            report.diverged.push(Divergence {
+                url: url.to_string(),
+                before: allowed_before,
+                before_line,
+                after: allowed_after,
+                after_line: matcher.matching_line(),
+            });
+        }
+    }
+    report
+}
+
+/// A fetched page, as seen by a crawler that ignores robots.txt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageSample {
+    pub url: String,
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// Value of the `X-Robots-Tag` response header, if any.
+    pub x_robots_tag: Option<String>,
+    /// `content` of the `<meta name="robots">` tag, if any.
+    pub meta_robots: Option<String>,
+}
+
+/// A contradiction between robots.txt and a page's indexing directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexabilityIssue {
+    /// The page is disallowed but serves `noindex`. Crawlers never fetch it,
+    /// never see the `noindex`, and may still index the URL from links.
+    NoindexNotSeen,
+    /// The page is disallowed but is an indexable 2xx page, so it may be
+    /// indexed without content (URL only, no title or snippet).
+    IndexedWithoutContent,
+    /// `X-Robots-Tag` and the meta robots tag disagree: one says `noindex`,
+    /// the other explicitly `index`.
+    ConflictingDirectives,
+}
+
+/// An indexability contradiction found by [`audit_indexability`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexabilityFinding {
+    pub url: String,
+    pub issue: IndexabilityIssue,
+    /// Line number of the Disallow rule, if robots.txt blocks the page.
+    pub line: Option<i32>,
+}
+
+/// Reports contradictions between `robots_txt` and the indexing directives
+/// of `samples` for `user_agent`, in sample order.
+///
+/// `X-Robots-Tag` values may scope directives to a crawler
+/// (`googlebot: noindex`); only unscoped directives and those scoped to
+/// `user_agent` are taken into account.
+pub fn audit_indexability(
+    robots_txt: &str,
+    user_agent: &str,
+    samples: &[PageSample],
+) -> Vec<IndexabilityFinding> {
+    let matcher = RobotsMatcher::new();
+    let mut findings = Vec::new();
+    for sample in samples {
+        let header = sample
+            .x_robots_tag
+            .as_deref()
+            .map(|v| index_directive(v, user_agent));
+        let meta = sample
+            .meta_robots
+            .as_deref()
+            .map(|v| index_directive(v, user_agent));
+        let noindex = header == Some(Some(false)) || meta == Some(Some(false));
+
+        let mut report = |issue, line| {
+            findings.push(IndexabilityFinding {
+                url: sample.url.clone(),
+                issue,
+                line,
+            })
+        };
+        if !matcher.is_allowed(robots_txt, user_agent, &sample.url) {
+            let line = Some(matcher.matching_line());
+            if noindex {
+                report(IndexabilityIssue::NoindexNotSeen, line);
+            } else if (200..300).contains(&sample.status) {
+                report(IndexabilityIssue::IndexedWithoutContent, line);
+            }
+        }
+        if let (Some(Some(h)), Some(Some(m))) = (header, meta) {
+            if h != m {
+                report(IndexabilityIssue::ConflictingDirectives, None);
+            }
+        }
+    }
+    findings
+}
+
+/// Robots meta tag and `X-Robots-Tag` directive names. Some take a value
+/// after a colon (`max-snippet:50`), which is not a crawler scope.
+const INDEX_DIRECTIVES: &[&str] = &[
+    "all",
+    "index",
+    "noindex",
+    "follow",
+    "nofollow",
+    "none",
+    "noarchive",
+    "nocache",
+    "nosnippet",
+    "noimageindex",
+    "notranslate",
+    "indexifembedded",
+    "max-snippet",
+    "max-image-preview",
+    "max-video-preview",
+    "unavailable_after",
+    "noodp",
+    "noydir",
+];
+
+fn is_index_directive(name: &str) -> bool {
+    INDEX_DIRECTIVES
+        .iter()
+        .any(|d| d.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Returns `Some(false)` if `value` contains `noindex` or `none` for
+/// `user_agent`, `Some(true)` for an explicit `index` or `all`, and `None`
+/// otherwise. `noindex` wins over `index`.
+///
+/// A `name:` prefix scopes the directives after it to a crawler only if
+/// `name` is not itself a directive and a directive follows the colon.
+fn index_directive(value: &str, user_agent: &str) -> Option<bool> {
+    let mut applies = true;
+    let mut result = None;
+    for token in value.split(',') {
+        let mut token = token.trim();
+        if let Some((scope, rest)) = token.split_once(':') {
+            let first = rest.split(':').next().unwrap_or_default();
+            if !is_index_directive(scope) && is_index_directive(first) {
+                applies = scope.trim().eq_ignore_ascii_case(user_agent);
+                token = rest.trim();
+            }
+        }
+        if !applies {
+            continue;
+        }
+        if token.eq_ignore_ascii_case("noindex") || token.eq_ignore_ascii_case("none") {
+            return Some(false);
+        }
+        if token.eq_ignore_ascii_case("index") || token.eq_ignore_ascii_case("all") {
+            result = Some(true);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls_blocked_by() {
+        let robots =
+            "User-agent: *\nDisallow: /shop/\nAllow: /shop/public/\nDisallow: /shop/cart/\n";
+        let urls = [
+            "https://example.com/shop/item",
+            "https://example.com/shop/public/item",
+            "https://example.com/shop/cart/1",
+            "https://example.com/about",
+        ];
+        assert_eq!(
+            urls_blocked_by(robots, "Googlebot", 2, urls),
+            ["https://example.com/shop/item"]
+        );
+        assert_eq!(
+            urls_blocked_by(robots, "Googlebot", 4, urls),
+            ["https://example.com/shop/cart/1"]
+        );
+        assert!(urls_blocked_by(robots, "Googlebot", 3, urls).is_empty());
+    }
+
+    #[test]
+    fn test_rank_rules_by_traffic() {
+        let robots = "User-agent: *\nDisallow: /search\nAllow: /search/help\nDisallow: /tmp/\n";
+        let log = [
+            ("https://example.com/search?q=1", 500),
+            ("https://example.com/search?q=2", 300),
+            ("https://example.com/search/help", 40),
+            ("https://example.com/tmp/a", 900),
+            ("https://example.com/", 10_000),
+        ];
+        let report = rank_rules_by_traffic(robots, "Googlebot", log);
+        assert_eq!(report.unmatched_urls, 1);
+        assert_eq!(report.unmatched_hits, 10_000);
+        let lines: Vec<i32> = report.rules.iter().map(|r| r.line).collect();
+        assert_eq!(lines, [4, 2, 3]);
+        assert_eq!(report.rules[1].urls, 2);
+        assert!(report.rules[2].allows);
+        assert_eq!(
+            report.to_csv(),
+            "line,decision,urls,hits\n4,disallow,1,900\n2,disallow,2,800\n3,allow,1,40\n"
+        );
+    }
+
+    #[test]
+    fn test_rank_ties_keep_line_order() {
+        let robots = "User-agent: *\nDisallow: /c\nDisallow: /a\nDisallow: /b\n";
+        let log = [
+            ("https://example.com/b", 5),
+            ("https://example.com/a", 5),
+            ("https://example.com/c", 5),
+        ];
+        for _ in 0..10 {
+            let report = rank_rules_by_traffic(robots, "Bot", log);
+            let lines: Vec<i32> = report.rules.iter().map(|r| r.line).collect();
+            assert_eq!(lines, [2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn test_audit_consistency() {
+        let robots =
+            "User-agent: Bingbot\nDisallow: /beta/\n\nUser-agent: *\nDisallow: /private/\n";
+        let sitemap = [
+            "https://example.com/",
+            "https://example.com/private/report",
+            "https://example.com/beta/page",
+        ];
+        let conflicts = audit_consistency(robots, sitemap);
+        assert_eq!(conflicts.len(), 4);
+        assert!(conflicts[..3]
+            .iter()
+            .all(|c| c.url.ends_with("/private/report") && c.line == 5));
+        assert_eq!(
+            conflicts[3],
+            SitemapConflict {
+                url: "https://example.com/beta/page".to_string(),
+                user_agent: "Bingbot".to_string(),
+                line: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_compare_policies() {
+        let before = "User-agent: *\nDisallow: /tmp/\n";
+        let after = "User-agent: *\nDisallow: /tmp/\nDisallow: /search\nAllow: /tmp/public/\n";
+        let urls = [
+            "https://example.com/",
+            "https://example.com/search?q=a",
+            "https://example.com/tmp/x",
+            "https://example.com/tmp/public/y",
+        ];
+        let report = compare_policies(before, after, "Googlebot", urls);
+        assert_eq!(report.checked, 4);
+        assert_eq!(
+            report.diverged,
+            [
+                Divergence {
+                    url: "https://example.com/search?q=a".to_string(),
+                    before: true,
+                    before_line: 0,
+                    after: false,
+                    after_line: 3,
+                },
+                Divergence {
+                    url: "https://example.com/tmp/public/y".to_string(),
+                    before: false,
+                    before_line: 2,
+                    after: true,
+                    after_line: 4,
+                },
+            ]
+        );
+        assert_eq!(report.divergence_rate(), 0.5);
+        assert_eq!(
+            compare_policies(before, before, "Googlebot", urls).diverged,
+            []
+        );
+    }
+
+    #[test]
+    fn test_audit_indexability() {
+        let robots = "User-agent: *\nDisallow: /private/\n";
+        let sample = |url: &str, header: Option<&str>, meta: Option<&str>| PageSample {
+            url: url.to_string(),
+            status: 200,
+            x_robots_tag: header.map(str::to_string),
+            meta_robots: meta.map(str::to_string),
+        };
+        let samples = [
+            sample("https://example.com/private/a", None, Some("noindex")),
+            sample("https://example.com/private/b", None, Some("index, follow")),
+            sample(
+                "https://example.com/c",
+                Some("googlebot: noindex"),
+                Some("index"),
+            ),
+            sample(
+                "https://example.com/d",
+                Some("bingbot: noindex"),
+                Some("index"),
+            ),
+            // Directives with values are not crawler scopes.
+            sample(
+                "https://example.com/private/e",
+                Some("max-snippet:50, noindex"),
+                None,
+            ),
+            sample(
+                "https://example.com/private/f",
+                Some("max-image-preview:large, max-video-preview:-1, noindex"),
+                None,
+            ),
+            sample(
+                "https://example.com/private/g",
+                Some("googlebot: max-snippet:-1, noindex"),
+                None,
+            ),
+            sample(
+                "https://example.com/private/h",
+                Some("bingbot: max-snippet:-1, noindex"),
+                None,
+            ),
+        ];
+        let issues: Vec<(IndexabilityIssue, Option<i32>)> =
+            audit_indexability(robots, "Googlebot", &samples)
+                .into_iter()
+                .map(|f| (f.issue, f.line))
+                .collect();
+        assert_eq!(
+            issues,
+            [
+                (IndexabilityIssue::NoindexNotSeen, Some(2)),
+                (IndexabilityIssue::IndexedWithoutContent, Some(2)),
+                (IndexabilityIssue::ConflictingDirectives, None),
+                (IndexabilityIssue::NoindexNotSeen, Some(2)),
+                (IndexabilityIssue::NoindexNotSeen, Some(2)),
+                (IndexabilityIssue::NoindexNotSeen, Some(2)),
+                (IndexabilityIssue::IndexedWithoutContent, Some(2)),
+            ]
+        );
+    }
+}
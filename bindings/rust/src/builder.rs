@@ -0,0 +1,276 @@
+//! Writing robots.txt files.
+//!
+//! [`RobotsBuilder`] assembles groups of rules and sitemaps and writes them
+//! as canonical text: one directive per line with the usual key spelling,
+//! groups in insertion order separated by a blank line, and sitemaps last.
+//! The parser reads the output back as the same groups, so generated files
+//! can be checked with [`RobotsDocument`] or a [`RobotsMatcher`](crate::RobotsMatcher).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::builder::RobotsBuilder;
+//!
+//! let robots_txt = RobotsBuilder::new()
+//!     .agent("GPTBot")
+//!     .disallow("/")
+//!     .agent("*")
+//!     .disallow("/admin/")
+//!     .crawl_delay(5.0)
+//!     .sitemap("https://example.com/sitemap.xml")
+//!     .build();
+//! assert_eq!(
+//!     robots_txt,
+//!     "User-agent: GPTBot\nDisallow: /\n\n\
+//!      User-agent: *\nDisallow: /admin/\nCrawl-delay: 5\n\n\
+//!      Sitemap: https://example.com/sitemap.xml\n"
+//! );
+//! ```
+
+use std::fmt;
+
+use crate::model::{RobotsDocument, Verb};
+
+/// A line of a group after its `User-agent` lines.
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Rule(Verb, String),
+    CrawlDelay(f64),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct BuilderGroup {
+    agents: Vec<String>,
+    lines: Vec<Line>,
+}
+
+impl BuilderGroup {
+    fn has_rules(&self) -> bool {
+        self.lines.iter().any(|line| matches!(line, Line::Rule(..)))
+    }
+}
+
+/// Builds a robots.txt one directive at a time.
+///
+/// Groups form the way the parser forms them: [`agent`](Self::agent) adds
+/// to the current group until it has an Allow or Disallow rule, then starts
+/// a new one. Rules and crawl delays added before any agent go to a `*`
+/// group.
+///
+/// Values are trimmed and cut at the first `#`, line break or NUL, where
+/// the parser would end them, so a value cannot add lines to the file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsBuilder {
+    groups: Vec<BuilderGroup>,
+    sitemaps: Vec<String>,
+}
+
+impl RobotsBuilder {
+    /// Creates an empty builder, which writes an empty file.
+    pub fn new() -> Self {
+        RobotsBuilder::default()
+    }
+
+    /// Adds a `User-agent` line.
+    pub fn agent(mut self, user_agent: &str) -> Self {
+        let user_agent = clean(user_agent);
+        match self.groups.last_mut() {
+            Some(group) if !group.has_rules() => group.agents.push(user_agent),
+            _ => self.groups.push(BuilderGroup {
+                agents: vec![user_agent],
+                lines: Vec::new(),
+            }),
+        }
+        self
+    }
+
+    /// Adds an `Allow` rule to the current group.
+    pub fn allow(self, pattern: &str) -> Self {
+        self.line(Line::Rule(Verb::Allow, clean(pattern)))
+    }
+
+    /// Adds a `Disallow` rule to the current group.
+    pub fn disallow(self, pattern: &str) -> Self {
+        self.line(Line::Rule(Verb::Disallow, clean(pattern)))
+    }
+
+    /// Adds a `Crawl-delay` line to the current group. Negative, infinite
+    /// and NaN delays are left out, as crawlers would ignore them.
+    pub fn crawl_delay(self, seconds: f64) -> Self {
+        if !(seconds.is_finite() && seconds >= 0.0) {
+            return self;
+        }
+        self.line(Line::CrawlDelay(seconds))
+    }
+
+    /// Adds a `Sitemap` line. Sitemaps belong to no group and are written
+    /// after all groups, in insertion order.
+    pub fn sitemap(mut self, url: &str) -> Self {
+        self.sitemaps.push(clean(url));
+        self
+    }
+
+    /// Returns the robots.txt text.
+    pub fn build(&self) -> String {
+        self.to_string()
+    }
+
+    fn line(mut self, line: Line) -> Self {
+        if self.groups.is_empty() {
+            self = self.agent("*");
+        }
+        let group = self.groups.last_mut().expect("a group was just added");
+        group.lines.push(line);
+        self
+    }
+}
+
+impl From<&RobotsDocument> for RobotsBuilder {
+    /// Copies the groups of a parsed robots.txt. Patterns keep the parser's
+    /// percent-escaping, and crawl delays and sitemaps, which
+    /// [`RobotsDocument`] does not hold, are not copied.
+    fn from(doc: &RobotsDocument) -> Self {
+        let groups = doc
+            .groups
+            .iter()
+            .map(|group| BuilderGroup {
+                agents: group.agents.clone(),
+                lines: group
+                    .rules
+                    .iter()
+                    .map(|rule| Line::Rule(rule.verb, rule.pattern.clone()))
+                    .collect(),
+            })
+            .collect();
+        RobotsBuilder {
+            groups,
+            sitemaps: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for RobotsBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, group) in self.groups.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for agent in &group.agents {
+                writeln!(f, "User-agent: {agent}")?;
+            }
+            for line in &group.lines {
+                match line {
+                    Line::Rule(Verb::Allow, pattern) => writeln!(f, "Allow: {pattern}")?,
+                    Line::Rule(Verb::Disallow, pattern) => writeln!(f, "Disallow: {pattern}")?,
+                    Line::CrawlDelay(seconds) => writeln!(f, "Crawl-delay: {seconds}")?,
+                }
+            }
+        }
+        if !self.sitemaps.is_empty() && !self.groups.is_empty() {
+            writeln!(f)?;
+        }
+        for url in &self.sitemaps {
+            writeln!(f, "Sitemap: {url}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Cuts `value` where the parser would end it and trims it.
+fn clean(value: &str) -> String {
+    let end = value.find(['#', '\r', '\n', '\0']).unwrap_or(value.len());
+    value[..end].trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Group, Rule};
+    use crate::{sitemaps, RobotsMatcher};
+
+    #[test]
+    fn test_build() {
+        let robots = RobotsBuilder::new()
+            .disallow("/tmp/ # scratch")
+            .agent("FooBot")
+            .agent("BarBot")
+            .crawl_delay(2.5)
+            .crawl_delay(f64::NAN)
+            .allow("/public")
+            .disallow("/\nDisallow: /injected")
+            .agent("BazBot")
+            .sitemap("https://example.com/a.xml")
+            .build();
+        assert_eq!(
+            robots,
+            "User-agent: *\nDisallow: /tmp/\n\n\
+             User-agent: FooBot\nUser-agent: BarBot\nCrawl-delay: 2.5\nAllow: /public\nDisallow: /\n\n\
+             User-agent: BazBot\n\n\
+             Sitemap: https://example.com/a.xml\n"
+        );
+        assert_eq!(RobotsBuilder::new().build(), "");
+        assert_eq!(
+            RobotsBuilder::new()
+                .sitemap("https://example.com/s.xml")
+                .build(),
+            "Sitemap: https://example.com/s.xml\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        if crate::missing_symbols().contains(&"robots_txt_get_directive") {
+            return;
+        }
+        let builder = RobotsBuilder::new()
+            .agent("GPTBot")
+            .disallow("/")
+            .agent("*")
+            .allow("/shop/public")
+            .disallow("/shop")
+            .crawl_delay(5.0)
+            .sitemap("https://example.com/sitemap.xml");
+        let robots = builder.build();
+
+        let doc = RobotsDocument::parse(&robots).unwrap();
+        let rule = |verb, pattern: &str, line| Rule {
+            verb,
+            pattern: pattern.into(),
+            line,
+        };
+        assert_eq!(
+            doc.groups,
+            [
+                Group {
+                    agents: vec!["GPTBot".into()],
+                    rules: vec![rule(Verb::Disallow, "/", 2)],
+                },
+                Group {
+                    agents: vec!["*".into()],
+                    rules: vec![
+                        rule(Verb::Allow, "/shop/public", 5),
+                        rule(Verb::Disallow, "/shop", 6),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(sitemaps(&robots), ["https://example.com/sitemap.xml"]);
+
+        let matcher = RobotsMatcher::new();
+        assert!(!matcher.is_allowed(&robots, "GPTBot", "https://example.com/"));
+        assert!(matcher.is_allowed(&robots, "FooBot", "https://example.com/shop/public/a"));
+        assert!(!matcher.is_allowed(&robots, "FooBot", "https://example.com/shop/b"));
+        assert_eq!(matcher.crawl_delay_for(&robots, "FooBot"), Some(5.0));
+
+        // Without crawl delays and sitemaps, the document writes the same
+        // groups back.
+        let rules_only = RobotsBuilder::new()
+            .agent("GPTBot")
+            .disallow("/")
+            .agent("*")
+            .allow("/shop/public")
+            .disallow("/shop")
+            .build();
+        assert_eq!(RobotsBuilder::from(&doc).build(), rules_only);
+    }
+}
@@ -0,0 +1,505 @@
+//! Per-origin cache of parsed robots.txt files.
+//!
+//! [`RobotsCache`] fetches each origin's robots.txt once with a
+//! [`RobotsFetcher`] and reuses it until it is older than the TTL (24 hours
+//! by default, the limit in RFC 9309 section 2.4). When the cache is full
+//! the least recently used origin is evicted.
+//!
+//! A failed fetch (see [`FetchOutcome::Unreachable`]) is cached only for
+//! the shorter error TTL, 5 minutes by default. If the origin's previous
+//! robots.txt is still cached, it stays in use meanwhile, as section 2.4
+//! allows; otherwise the origin is disallowed until the retry.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::cache::{FetchOutcome, RobotsCache};
+//!
+//! let cache = RobotsCache::new(|robots_url: &str| {
+//!     // Fetch robots_url with the HTTP client of your choice.
+//!     match robots_url {
+//!         "https://example.com/robots.txt" => FetchOutcome::Found("User-agent: *\nDisallow: /tmp/\n".into()),
+//!         _ => FetchOutcome::Unavailable,
+//!     }
+//! });
+//! assert!(!cache.is_allowed("MyBot", "https://example.com/tmp/x"));
+//! assert!(cache.is_allowed("MyBot", "https://other.example/tmp/x"));
+//! ```
+//!
+//! With an async HTTP client, implement [`AsyncRobotsFetcher`] instead and
+//! call [`RobotsCache::is_allowed_async`]. The cache does not depend on a
+//! runtime or client.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+use crate::normalize::normalize_authority;
+use crate::security::{SecurityProfile, MAX_ROBOTS_TXT_BYTES};
+use crate::RobotsTxt;
+
+/// Result of fetching a robots.txt, classified as in RFC 9309 section 2.3.1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchOutcome {
+    /// The server returned the file (2xx, after redirects).
+    Found(String),
+    /// The file does not exist (4xx); every URL is allowed.
+    Unavailable,
+    /// The server or network failed (5xx, timeout); every URL is
+    /// disallowed, unless an earlier robots.txt of the origin is cached.
+    Unreachable,
+}
+
+/// Fetches robots.txt files for a [`RobotsCache`].
+///
+/// Implemented for closures taking the robots.txt URL.
+pub trait RobotsFetcher {
+    /// Fetches `robots_url`, e.g. `https://example.com/robots.txt`.
+    fn fetch(&self, robots_url: &str) -> FetchOutcome;
+}
+
+impl<F: Fn(&str) -> FetchOutcome> RobotsFetcher for F {
+    fn fetch(&self, robots_url: &str) -> FetchOutcome {
+        self(robots_url)
+    }
+}
+
+/// Fetches robots.txt files for [`RobotsCache::is_allowed_async`].
+///
+/// Implementations can use `async fn`. Map client errors to
+/// [`FetchOutcome::Unreachable`].
+///
+/// ```no_run
+/// use robotstxt::cache::{AsyncRobotsFetcher, FetchOutcome};
+///
+/// struct Fetcher;
+///
+/// impl AsyncRobotsFetcher for Fetcher {
+///     async fn fetch(&self, robots_url: &str) -> FetchOutcome {
+///         // e.g. a hyper client with your own connection pool
+///         FetchOutcome::Unavailable
+///     }
+/// }
+/// ```
+pub trait AsyncRobotsFetcher {
+    /// Fetches `robots_url`, e.g. `https://example.com/robots.txt`.
+    fn fetch(&self, robots_url: &str) -> impl Future<Output = FetchOutcome> + Send;
+}
+
+#[derive(Clone)]
+enum Policy {
+    Parsed(Arc<RobotsTxt>),
+    AllowAll,
+    DisallowAll,
+}
+
+struct Entry {
+    policy: Policy,
+    fetched_at: Instant,
+    ttl: Duration,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    by_origin: HashMap<String, Entry>,
+    // last_used tick -> origin, oldest first.
+    lru: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Entries {
+    fn touch(&mut self, origin: &str) {
+        self.tick += 1;
+        if let Some(entry) = self.by_origin.get_mut(origin) {
+            self.lru.remove(&entry.last_used);
+            entry.last_used = self.tick;
+            self.lru.insert(self.tick, origin.to_string());
+        }
+    }
+
+    fn remove(&mut self, origin: &str) {
+        if let Some(entry) = self.by_origin.remove(origin) {
+            self.lru.remove(&entry.last_used);
+        }
+    }
+}
+
+/// Caches one robots.txt per origin (scheme, host and port).
+///
+/// The cache is `Sync` if the fetcher and clock are. The lock is not held
+/// while fetching, so two threads missing the same origin at once may
+/// both fetch it.
+pub struct RobotsCache<F, C = SystemClock> {
+    fetcher: F,
+    clock: C,
+    ttl: Duration,
+    error_ttl: Duration,
+    max_entries: usize,
+    profile: SecurityProfile,
+    entries: Mutex<Entries>,
+}
+
+impl<F> RobotsCache<F> {
+    /// Creates a cache with a 24 hour TTL, a 5 minute error TTL and room for
+    /// 10,000 origins.
+    pub fn new(fetcher: F) -> Self {
+        RobotsCache {
+            fetcher,
+            clock: SystemClock,
+            ttl: Duration::from_secs(24 * 60 * 60),
+            error_ttl: Duration::from_secs(5 * 60),
+            max_entries: 10_000,
+            profile: SecurityProfile::Standard,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+}
+
+impl<F, C: Clock> RobotsCache<F, C> {
+    /// Sets how long a fetched robots.txt is used before it is fetched
+    /// again.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how long after an [`Unreachable`](FetchOutcome::Unreachable)
+    /// fetch the robots.txt is fetched again.
+    pub fn with_error_ttl(mut self, error_ttl: Duration) -> Self {
+        self.error_ttl = error_ttl;
+        self
+    }
+
+    /// Sets the number of origins kept; at least one is always kept.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Parses and checks as `profile` prescribes; see
+    /// [`SecurityProfile::Paranoid`]. Applies to robots.txt files fetched
+    /// afterwards.
+    pub fn with_security_profile(mut self, profile: SecurityProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Uses `clock` to expire entries.
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> RobotsCache<F, C2> {
+        RobotsCache {
+            fetcher: self.fetcher,
+            clock,
+            ttl: self.ttl,
+            error_ttl: self.error_ttl,
+            max_entries: self.max_entries,
+            profile: self.profile,
+            entries: self.entries,
+        }
+    }
+
+    /// Returns the number of cached origins, including expired ones not yet
+    /// refetched.
+    pub fn len(&self) -> usize {
+        self.lock().by_origin.len()
+    }
+
+    /// Returns true if no origin is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every cached robots.txt.
+    pub fn clear(&self) {
+        *self.lock() = Entries::default();
+    }
+
+    fn cached(&self, origin: &str, now: Instant) -> Option<Policy> {
+        let mut entries = self.lock();
+        let entry = entries.by_origin.get(origin)?;
+        if now.saturating_duration_since(entry.fetched_at) >= entry.ttl {
+            return None;
+        }
+        let policy = entry.policy.clone();
+        entries.touch(origin);
+        Some(policy)
+    }
+
+    fn store(&self, origin: &str, now: Instant, outcome: FetchOutcome) -> Policy {
+        let unreachable = outcome == FetchOutcome::Unreachable;
+        let mut policy = match outcome {
+            FetchOutcome::Found(body) => match self.profile {
+                SecurityProfile::Paranoid => {
+                    let mut end = body.len().min(MAX_ROBOTS_TXT_BYTES);
+                    while !body.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    RobotsTxt::try_parse_strict(&body[..end])
+                        .map_or(Policy::DisallowAll, |robots| {
+                            Policy::Parsed(Arc::new(robots))
+                        })
+                }
+                SecurityProfile::Standard => Policy::Parsed(Arc::new(RobotsTxt::parse(&body))),
+            },
+            FetchOutcome::Unavailable => Policy::AllowAll,
+            FetchOutcome::Unreachable => Policy::DisallowAll,
+        };
+        let ttl = if unreachable {
+            self.error_ttl
+        } else {
+            self.ttl
+        };
+
+        let mut entries = self.lock();
+        if unreachable {
+            // Keep the last good robots.txt through a failed refresh.
+            if let Some(Entry {
+                policy: previous @ Policy::Parsed(_),
+                ..
+            }) = entries.by_origin.get(origin)
+            {
+                policy = previous.clone();
+            }
+        }
+        entries.remove(origin);
+        while entries.by_origin.len() >= self.max_entries {
+            let Some((_, oldest)) = entries.lru.pop_first() else {
+                break;
+            };
+            entries.by_origin.remove(&oldest);
+        }
+        entries.by_origin.insert(
+            origin.to_string(),
+            Entry {
+                policy: policy.clone(),
+                fetched_at: now,
+                ttl,
+                last_used: 0,
+            },
+        );
+        entries.touch(origin);
+        policy
+    }
+
+    /// Returns the origin of `url`, or None if the URL is disallowed
+    /// without looking at a robots.txt.
+    fn origin_to_check(&self, url: &str) -> Option<String> {
+        if let Some(limit) = self.profile.url_limit() {
+            if url.len() > limit.max_len {
+                return None;
+            }
+        }
+        origin_of(url)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<F: RobotsFetcher, C: Clock> RobotsCache<F, C> {
+    /// Checks if `url` is allowed for `user_agent` by the robots.txt of its
+    /// origin, fetching it if it is not cached or has expired.
+    ///
+    /// URLs that are not absolute `http` or `https` URLs, or are longer
+    /// than the security profile allows, are disallowed.
+    pub fn is_allowed(&self, user_agent: &str, url: &str) -> bool {
+        let Some(origin) = self.origin_to_check(url) else {
+            return false;
+        };
+        let now = self.clock.now();
+        let policy = match self.cached(&origin, now) {
+            Some(policy) => policy,
+            None => {
+                let outcome = self.fetcher.fetch(&robots_url(&origin));
+                self.store(&origin, now, outcome)
+            }
+        };
+        policy.is_allowed(user_agent, url)
+    }
+}
+
+impl<F: AsyncRobotsFetcher, C: Clock> RobotsCache<F, C> {
+    /// Same as [`is_allowed`](Self::is_allowed), fetching with an
+    /// [`AsyncRobotsFetcher`]. No lock is held across the fetch.
+    pub async fn is_allowed_async(&self, user_agent: &str, url: &str) -> bool {
+        let Some(origin) = self.origin_to_check(url) else {
+            return false;
+        };
+        let now = self.clock.now();
+        let policy = match self.cached(&origin, now) {
+            Some(policy) => policy,
+            None => {
+                let outcome = self.fetcher.fetch(&robots_url(&origin)).await;
+                self.store(&origin, now, outcome)
+            }
+        };
+        policy.is_allowed(user_agent, url)
+    }
+}
+
+impl Policy {
+    fn is_allowed(&self, user_agent: &str, url: &str) -> bool {
+        match self {
+            Policy::Parsed(robots) => robots.is_allowed(user_agent, url),
+            Policy::AllowAll => true,
+            Policy::DisallowAll => false,
+        }
+    }
+}
+
+fn robots_url(origin: &str) -> String {
+    format!("{origin}/robots.txt")
+}
+
+/// Returns `scheme://host[:port]` for an http(s) URL, without userinfo
+/// or a default port.
+fn origin_of(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = normalize_authority(&scheme, authority);
+    if host.is_empty() {
+        return None;
+    }
+    Some(format!("{scheme}://{host}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::cell::{Cell, RefCell};
+
+    #[test]
+    fn test_robots_cache() {
+        let fetched = RefCell::new(Vec::new());
+        let fetcher = |url: &str| {
+            fetched.borrow_mut().push(url.to_string());
+            match url {
+                "https://a.com/robots.txt" => {
+                    FetchOutcome::Found("User-agent: *\nDisallow: /x\n".into())
+                }
+                "https://b.com/robots.txt" => FetchOutcome::Unavailable,
+                _ => FetchOutcome::Unreachable,
+            }
+        };
+        let clock = ManualClock::new();
+        let cache = RobotsCache::new(fetcher)
+            .with_max_entries(2)
+            .with_clock(clock.clone());
+
+        assert!(!cache.is_allowed("FooBot", "https://a.com/x"));
+        assert!(cache.is_allowed("FooBot", "https://A.com:443/y"));
+        assert!(cache.is_allowed("FooBot", "https://b.com/x"));
+        assert!(!cache.is_allowed("FooBot", "ftp://a.com/"));
+        assert_eq!(
+            *fetched.borrow(),
+            ["https://a.com/robots.txt", "https://b.com/robots.txt"]
+        );
+
+        // a.com was used more recently than b.com, so b.com is evicted.
+        assert!(!cache.is_allowed("FooBot", "https://a.com/x"));
+        assert!(!cache.is_allowed("FooBot", "http://c.com/"));
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.is_allowed("FooBot", "https://a.com/x"));
+        assert_eq!(fetched.borrow().len(), 3);
+
+        clock.advance(Duration::from_secs(24 * 60 * 60));
+        assert!(!cache.is_allowed("FooBot", "https://a.com/x"));
+        assert_eq!(fetched.borrow().len(), 4);
+        assert_eq!(fetched.borrow()[2], "http://c.com/robots.txt");
+    }
+
+    #[test]
+    fn test_paranoid_profile() {
+        let fetcher = |url: &str| match url {
+            "https://a.com/robots.txt" => {
+                FetchOutcome::Found("User-agent: *\nDissalow: /x\nDisallow: /y\n".into())
+            }
+            _ => FetchOutcome::Found(format!(
+                "User-agent: *\nAllow: /\n{}Disallow: /\n",
+                "#".repeat(MAX_ROBOTS_TXT_BYTES)
+            )),
+        };
+        let cache = RobotsCache::new(fetcher).with_security_profile(SecurityProfile::Paranoid);
+        assert!(cache.is_allowed("FooBot", "https://a.com/x"));
+        assert!(!cache.is_allowed("FooBot", "https://a.com/y"));
+        assert!(cache.is_allowed("FooBot", "https://b.com/y"));
+        let long = format!("https://a.com/{}", "a".repeat(2083));
+        assert!(!cache.is_allowed("FooBot", &long));
+    }
+
+    #[test]
+    fn test_is_allowed_async() {
+        struct Fetcher;
+        impl AsyncRobotsFetcher for Fetcher {
+            async fn fetch(&self, robots_url: &str) -> FetchOutcome {
+                assert_eq!(robots_url, "http://a.com:8080/robots.txt");
+                FetchOutcome::Found("User-agent: *\nDisallow: /x\n".into())
+            }
+        }
+        let cache = RobotsCache::new(Fetcher);
+        let mut check = Box::pin(cache.is_allowed_async("FooBot", "http://a.com:8080/x"));
+        let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+        assert_eq!(check.as_mut().poll(&mut cx), std::task::Poll::Ready(false));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let up = Cell::new(true);
+        let fetches = Cell::new(0);
+        let fetcher = |url: &str| {
+            fetches.set(fetches.get() + 1);
+            match (url, up.get()) {
+                ("https://a.com/robots.txt", true) => {
+                    FetchOutcome::Found("User-agent: *\nDisallow: /x\n".into())
+                }
+                ("https://b.com/robots.txt", true) => FetchOutcome::Unavailable,
+                _ => FetchOutcome::Unreachable,
+            }
+        };
+        let clock = ManualClock::new();
+        let cache = RobotsCache::new(fetcher)
+            .with_ttl(Duration::from_secs(100))
+            .with_error_ttl(Duration::from_secs(10))
+            .with_clock(clock.clone());
+
+        // Without an earlier copy, the origin is disallowed until the retry
+        // after the error TTL.
+        up.set(false);
+        assert!(!cache.is_allowed("FooBot", "https://a.com/y"));
+        clock.advance(Duration::from_secs(9));
+        assert!(!cache.is_allowed("FooBot", "https://a.com/y"));
+        assert_eq!(fetches.get(), 1);
+        up.set(true);
+        clock.advance(Duration::from_secs(1));
+        assert!(cache.is_allowed("FooBot", "https://a.com/y"));
+        assert_eq!(fetches.get(), 2);
+
+        // A failed refresh keeps the last robots.txt, retried after the
+        // error TTL.
+        up.set(false);
+        clock.advance(Duration::from_secs(100));
+        assert!(cache.is_allowed("FooBot", "https://a.com/y"));
+        assert!(!cache.is_allowed("FooBot", "https://a.com/x"));
+        assert_eq!(fetches.get(), 3);
+        clock.advance(Duration::from_secs(10));
+        assert!(cache.is_allowed("FooBot", "https://a.com/y"));
+        assert_eq!(fetches.get(), 4);
+
+        // Only a parsed robots.txt is kept; a 4xx is not a copy to fall
+        // back on.
+        up.set(true);
+        assert!(cache.is_allowed("FooBot", "https://b.com/x"));
+        up.set(false);
+        clock.advance(Duration::from_secs(100));
+        assert!(!cache.is_allowed("FooBot", "https://b.com/x"));
+    }
+}
@@ -0,0 +1,90 @@
+//! Time source for components that expire or space out work.
+//!
+//! Time-dependent types take a [`Clock`] so tests can move time forward
+//! with a [`ManualClock`] instead of sleeping.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use robotstxt::clock::{Clock, ManualClock};
+//!
+//! let clock = ManualClock::new();
+//! let start = clock.now();
+//! clock.advance(Duration::from_secs(3600));
+//! assert_eq!(clock.now() - start, Duration::from_secs(3600));
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at the current time.
+    pub fn new() -> Self {
+        ManualClock {
+            start: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// Moves the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new();
+        let shared = clock.clone();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+        shared.advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - start, Duration::from_secs(5));
+        assert!(SystemClock.now() >= start);
+    }
+}
@@ -0,0 +1,141 @@
+//! APIs of other robots.txt crates, so code written against them builds
+//! against this crate with few or no changes.
+//!
+//! [`DefaultMatcher`] mirrors the pure-Rust `robotstxt` crate:
+//!
+//! ```no_run
+//! use robotstxt::DefaultMatcher;
+//!
+//! let mut matcher = DefaultMatcher::default();
+//! let robots = "User-agent: *\nDisallow: /private\n";
+//! assert!(!matcher.one_agent_allowed_by_robots(robots, "Googlebot", "https://example.com/private"));
+//! assert!(matcher.allowed_by_robots(robots, vec!["Googlebot"], "https://example.com/"));
+//! ```
+//!
+//! The methods take `&mut self` as in that crate, although
+//! [`RobotsMatcher`] itself only needs `&self`.
+//!
+//! [`Robot`] mirrors `texting_robots`:
+//!
+//! ```no_run
+//! use robotstxt::Robot;
+//!
+//! let robot = Robot::new("FooBot", b"User-agent: *\nCrawl-delay: 2\nDisallow: /private\n").unwrap();
+//! assert!(!robot.allowed("https://example.com/private"));
+//! assert_eq!(robot.delay, Some(2.0));
+//! ```
+
+use crate::{check_no_nul, RobotsError, RobotsMatcher, RobotsTxt};
+
+/// A matcher with the method names and signatures of the pure-Rust
+/// crate's `DefaultMatcher`.
+#[derive(Default)]
+pub struct DefaultMatcher {
+    matcher: RobotsMatcher,
+}
+
+impl DefaultMatcher {
+    /// Returns true if `url` is allowed for any of `user_agents`, as
+    /// [`RobotsMatcher::is_allowed_any`].
+    pub fn allowed_by_robots(
+        &mut self,
+        robots_body: &str,
+        user_agents: Vec<&str>,
+        url: &str,
+    ) -> bool {
+        self.matcher.is_allowed_any(robots_body, &user_agents, url)
+    }
+
+    /// Returns true if `url` is allowed for `user_agent`.
+    pub fn one_agent_allowed_by_robots(
+        &mut self,
+        robots_txt: &str,
+        user_agent: &str,
+        url: &str,
+    ) -> bool {
+        self.matcher.is_allowed(robots_txt, user_agent, url)
+    }
+
+    /// Line number of the rule that decided the last check, or 0.
+    pub fn matching_line(&self) -> u32 {
+        self.matcher.matching_line().max(0) as u32
+    }
+
+    /// Whether the last robots.txt had a group for a specific user-agent.
+    pub fn ever_seen_specific_agent(&self) -> bool {
+        self.matcher.ever_seen_specific_agent()
+    }
+
+    /// The underlying matcher, for the APIs only this crate has.
+    pub fn inner(&self) -> &RobotsMatcher {
+        &self.matcher
+    }
+}
+
+/// A robots.txt parsed for one user-agent, with the fields and methods of
+/// `texting_robots::Robot`.
+pub struct Robot {
+    /// Crawl-delay in seconds for the agent, taken from the `*` group if
+    /// the agent's group has none.
+    pub delay: Option<f32>,
+    /// `Sitemap:` URLs, in file order.
+    pub sitemaps: Vec<String>,
+    agent: String,
+    robots: RobotsTxt,
+}
+
+impl Robot {
+    /// Parses `robots_txt` for `agent`.
+    ///
+    /// Invalid UTF-8 in `robots_txt` is replaced with U+FFFD. Fails with
+    /// [`RobotsError::InteriorNul`] if either input contains a NUL byte.
+    pub fn new(agent: &str, robots_txt: &[u8]) -> Result<Robot, RobotsError> {
+        check_no_nul("user_agent", agent)?;
+        let robots = RobotsTxt::try_parse(&String::from_utf8_lossy(robots_txt))?;
+        let matcher = RobotsMatcher::try_new()?;
+        matcher.is_allowed_parsed(&robots, agent, "/");
+        Ok(Robot {
+            delay: matcher.crawl_delay().map(|delay| delay as f32),
+            sitemaps: robots.sitemaps(),
+            agent: agent.to_string(),
+            robots,
+        })
+    }
+
+    /// Returns true if the agent may fetch `url`.
+    pub fn allowed(&self, url: &str) -> bool {
+        self.robots.is_allowed(&self.agent, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_matcher() {
+        let mut matcher = DefaultMatcher::default();
+        let robots = "User-agent: FooBot\nDisallow: /\n\nUser-agent: *\nAllow: /\nDisallow: /x\n";
+        assert!(!matcher.one_agent_allowed_by_robots(robots, "FooBot", "http://foo.bar/"));
+        assert_eq!(matcher.matching_line(), 2);
+        assert!(matcher.ever_seen_specific_agent());
+        assert!(matcher.allowed_by_robots(robots, vec!["BarBot"], "http://foo.bar/"));
+        assert!(!matcher.allowed_by_robots(robots, vec!["BarBot"], "http://foo.bar/x"));
+        assert_eq!(matcher.matching_line(), 6);
+    }
+
+    #[test]
+    fn test_robot() {
+        let robots = b"User-agent: *\nCrawl-delay: 1.5\nDisallow: /x\n\n\
+                       User-agent: FooBot\nDisallow: /y\n\
+                       Sitemap: https://foo.bar/sitemap.xml\n";
+        let robot = Robot::new("FooBot", robots).unwrap();
+        assert!(robot.allowed("http://foo.bar/x"));
+        assert!(!robot.allowed("http://foo.bar/y"));
+        assert_eq!(robot.delay, Some(1.5));
+        if !crate::missing_symbols().contains(&"robots_txt_sitemap") {
+            assert_eq!(robot.sitemaps, ["https://foo.bar/sitemap.xml"]);
+        }
+        assert!(Robot::new("Foo\0Bot", robots).is_err());
+    }
+}
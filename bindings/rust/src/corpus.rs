@@ -0,0 +1,95 @@
+//! Parallel evaluation of one URL against many robots.txt files.
+//!
+//! Requires the `rayon` feature. Work is spread over rayon's global thread
+//! pool; each file is checked with a matcher borrowed from a
+//! [`MatcherPool`], so no more matchers are created than files are checked
+//! at once, at most one per worker thread.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::corpus::evaluate_corpus;
+//!
+//! let files = ["User-agent: *\nDisallow: /\n", "User-agent: *\nAllow: /\n"];
+//! let stats = evaluate_corpus(&files, "Googlebot", "/");
+//! assert_eq!((stats.allowed, stats.disallowed), (1, 1));
+//! ```
+
+use rayon::prelude::*;
+
+use crate::MatcherPool;
+
+/// Counts over a corpus from [`evaluate_corpus`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CorpusStats {
+    pub files: usize,
+    pub allowed: usize,
+    pub disallowed: usize,
+    /// Files with a group naming the agent, rather than only `*`.
+    pub specific_agent: usize,
+    /// Files that give the agent a Crawl-delay.
+    pub crawl_delay: usize,
+}
+
+impl CorpusStats {
+    fn add(self, other: CorpusStats) -> CorpusStats {
+        CorpusStats {
+            files: self.files + other.files,
+            allowed: self.allowed + other.allowed,
+            disallowed: self.disallowed + other.disallowed,
+            specific_agent: self.specific_agent + other.specific_agent,
+            crawl_delay: self.crawl_delay + other.crawl_delay,
+        }
+    }
+}
+
+/// Checks `url` for `user_agent` against every file in `robots_files` in
+/// parallel.
+pub fn evaluate_corpus<T>(robots_files: &[T], user_agent: &str, url: &str) -> CorpusStats
+where
+    T: AsRef<str> + Sync,
+{
+    let pool = MatcherPool::with_max_idle(rayon::current_num_threads());
+    robots_files
+        .par_iter()
+        .map(|robots| {
+            pool.with(|matcher| {
+                let allowed = matcher.is_allowed(robots.as_ref(), user_agent, url);
+                CorpusStats {
+                    files: 1,
+                    allowed: usize::from(allowed),
+                    disallowed: usize::from(!allowed),
+                    specific_agent: usize::from(matcher.ever_seen_specific_agent()),
+                    crawl_delay: usize::from(matcher.crawl_delay().is_some()),
+                }
+            })
+        })
+        .reduce(CorpusStats::default, CorpusStats::add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_corpus() {
+        let files: Vec<String> = (0..100)
+            .map(|i| match i % 4 {
+                0 => "User-agent: FooBot\nDisallow: /\n".to_string(),
+                1 => "User-agent: *\nCrawl-delay: 1\nDisallow: /x\n".to_string(),
+                _ => String::new(),
+            })
+            .collect();
+        let stats = evaluate_corpus(&files, "FooBot", "http://foo.bar/x");
+        assert_eq!(
+            stats,
+            CorpusStats {
+                files: 100,
+                allowed: 50,
+                disallowed: 50,
+                specific_agent: 25,
+                crawl_delay: 25,
+            }
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Loading `librobots` at runtime instead of linking it (`dlopen` feature).
+//!
+//! The library is loaded on first use from the path in the
+//! `ROBOTS_LIBRARY_PATH` environment variable, or by its platform file name
+//! (`librobots.so`, `librobots.dylib`, `robots.dll`) from the system search
+//! path. Call [`load_library`] before any other API to choose the path in
+//! code and to handle a missing library or symbol as an error, or
+//! [`ensure_loaded`] to get the error for the default path.
+//!
+//! A failed load is not remembered: the next call tries again, so a
+//! library installed or a path corrected later is picked up. Until a load
+//! succeeds, fallible APIs such as [`RobotsMatcher::try_new`](crate::RobotsMatcher::try_new)
+//! return the load error and the infallible ones panic with it.
+
+use std::ffi::c_void;
+use std::os::raw::{c_char, c_double, c_int};
+use std::sync::{Mutex, OnceLock};
+
+use libloading::Library;
+
+use crate::{RequestRate, RobotsError, RobotsMatcherOpaque};
+
+/// Environment variable naming the library to load on first use.
+pub const LIBRARY_PATH_ENV: &str = "ROBOTS_LIBRARY_PATH";
+
+static LOADED: OnceLock<Loaded> = OnceLock::new();
+// Held while loading, so two threads do not both open the library.
+static LOADING: Mutex<()> = Mutex::new(());
+
+struct Loaded {
+    library: Library,
+    core: Core,
+}
+
+macro_rules! required_symbols {
+    ($(fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        struct Core {
+            $($name: unsafe extern "C" fn($($ty),*) -> $ret,)*
+        }
+
+        impl Core {
+            fn resolve(library: &Library) -> Result<Core, RobotsError> {
+                // SAFETY: the symbols are declared with these signatures in robots_c.h.
+                unsafe {
+                    Ok(Core {
+                        $($name: *library
+                            .get(concat!(stringify!($name), "\0").as_bytes())
+                            .map_err(|_| RobotsError::MissingSymbol {
+                                symbol: stringify!($name),
+                            })?,)*
+                    })
+                }
+            }
+        }
+
+        $(
+            #[allow(clippy::too_many_arguments)]
+            pub(crate) unsafe fn $name($($arg: $ty),*) -> $ret {
+                (loaded().core.$name)($($arg),*)
+            }
+        )*
+    };
+}
+
+required_symbols! {
+    fn robots_matcher_create() -> *mut RobotsMatcherOpaque;
+    fn robots_matcher_free(matcher: *mut RobotsMatcherOpaque) -> ();
+    fn robots_allowed_by_robots(
+        matcher: *mut RobotsMatcherOpaque,
+        robots_txt: *const c_char,
+        robots_txt_len: usize,
+        user_agent: *const c_char,
+        user_agent_len: usize,
+        url: *const c_char,
+        url_len: usize
+    ) -> bool;
+    fn robots_allowed_by_robots_multi(
+        matcher: *mut RobotsMatcherOpaque,
+        robots_txt: *const c_char,
+        robots_txt_len: usize,
+        user_agents: *const *const c_char,
+        user_agent_lens: *const usize,
+        num_user_agents: usize,
+        url: *const c_char,
+        url_len: usize
+    ) -> bool;
+    fn robots_matching_line(matcher: *const RobotsMatcherOpaque) -> c_int;
+    fn robots_ever_seen_specific_agent(matcher: *const RobotsMatcherOpaque) -> bool;
+    fn robots_has_crawl_delay(matcher: *const RobotsMatcherOpaque) -> bool;
+    fn robots_get_crawl_delay(matcher: *const RobotsMatcherOpaque) -> c_double;
+    fn robots_has_request_rate(matcher: *const RobotsMatcherOpaque) -> bool;
+    fn robots_get_request_rate(
+        matcher: *const RobotsMatcherOpaque,
+        rate: *mut RequestRate
+    ) -> bool;
+    fn robots_is_valid_user_agent(user_agent: *const c_char, len: usize) -> bool;
+    fn robots_version() -> *const c_char;
+}
+
+/// Loads the robots library from `path`.
+///
+/// Fails if the library cannot be loaded, lacks one of the functions every
+/// binding version needs, or was already loaded (explicitly or by an
+/// earlier call into the crate). Optional functions missing from an older
+/// library are reported by [`missing_symbols`](crate::missing_symbols).
+pub fn load_library(path: &str) -> Result<(), RobotsError> {
+    load_into(&LOADED, path)
+}
+
+/// Loads the library from `ROBOTS_LIBRARY_PATH` or the system search path
+/// unless one is already loaded, and returns the error if that fails.
+pub fn ensure_loaded() -> Result<(), RobotsError> {
+    try_loaded().map(|_| ())
+}
+
+fn load_into(cell: &OnceLock<Loaded>, path: &str) -> Result<(), RobotsError> {
+    let _loading = LOADING.lock().unwrap_or_else(|e| e.into_inner());
+    if cell.get().is_some() {
+        return Err(RobotsError::LibraryLoad {
+            path: path.to_string(),
+            message: "a robots library is already loaded".to_string(),
+        });
+    }
+    let loaded = open(path)?;
+    let _ = cell.set(loaded);
+    Ok(())
+}
+
+fn default_path() -> String {
+    std::env::var(LIBRARY_PATH_ENV).unwrap_or_else(|_| {
+        libloading::library_filename("robots")
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+fn open(path: &str) -> Result<Loaded, RobotsError> {
+    // SAFETY: loading runs the library's initializers, which for librobots
+    // only set up static data.
+    let library = unsafe { Library::new(path) }.map_err(|e| RobotsError::LibraryLoad {
+        path: path.to_string(),
+        message: e.to_string(),
+    })?;
+    let core = Core::resolve(&library)?;
+    Ok(Loaded { library, core })
+}
+
+/// Returns the loaded library, loading it from the default path first if
+/// needed.
+fn try_loaded() -> Result<&'static Loaded, RobotsError> {
+    if let Some(loaded) = LOADED.get() {
+        return Ok(loaded);
+    }
+    let result = load_into(&LOADED, &default_path());
+    // Another thread may have loaded it while this one waited for the lock.
+    match LOADED.get() {
+        Some(loaded) => Ok(loaded),
+        None => Err(result.expect_err("a successful load sets LOADED")),
+    }
+}
+
+fn loaded() -> &'static Loaded {
+    try_loaded().unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Looks up `name` (NUL-terminated) in the loaded library, or returns None
+/// if no library can be loaded.
+pub(crate) fn lookup(name: &str) -> Option<*mut c_void> {
+    let loaded = try_loaded().ok()?;
+    // SAFETY: only the address is read; callers cast it to the declared type.
+    unsafe {
+        Some(
+            loaded
+                .library
+                .get::<*mut c_void>(name.as_bytes())
+                .map_or(std::ptr::null_mut(), |symbol| *symbol),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_load_is_retried() {
+        let cell = OnceLock::new();
+        let err = load_into(&cell, "/no/such/librobots.so").unwrap_err();
+        assert!(matches!(
+            err,
+            RobotsError::LibraryLoad { ref path, .. } if path == "/no/such/librobots.so"
+        ));
+        assert!(cell.get().is_none());
+
+        load_into(&cell, &default_path()).unwrap();
+        let err = load_into(&cell, &default_path()).unwrap_err();
+        assert!(err.to_string().contains("already loaded"));
+    }
+}
@@ -0,0 +1,88 @@
+//! Error type for the fallible `try_*` APIs.
+
+use std::fmt;
+
+/// Errors returned by the fallible matcher APIs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RobotsError {
+    /// The URL exceeds the configured maximum length and the matcher is set
+    /// to [`UrlLengthPolicy::Reject`](crate::UrlLengthPolicy::Reject).
+    UrlTooLong { len: usize, max: usize },
+    /// The URL has a scheme other than `http` or `https`, such as `ftp:`,
+    /// `mailto:` or `javascript:`. robots.txt only governs HTTP(S) URLs.
+    UnsupportedScheme { scheme: String },
+    /// The loaded robots library is older than this binding and does not
+    /// provide the C function `symbol`.
+    Unsupported { symbol: &'static str },
+    /// The robots library could not be loaded at runtime (`dlopen` feature).
+    LibraryLoad { path: String, message: String },
+    /// The runtime-loaded robots library lacks the required C function
+    /// `symbol`, so it is not a robots library or is too old to use.
+    MissingSymbol { symbol: &'static str },
+    /// The input string `field` contains a NUL byte. The C++ parser stops
+    /// reading a line at NUL, so the decision would not reflect the input.
+    InteriorNul { field: &'static str },
+    /// The C++ matcher could not be allocated.
+    NullMatcher,
+}
+
+/// How a caller such as a URL scheduler should treat a [`RobotsError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// May succeed if retried later.
+    Transient,
+    /// Fails again for the same input and library; drop the URL or fix the
+    /// deployment.
+    Permanent,
+    /// Refused by the crawler's own configuration, such as a URL limit.
+    PolicyDenied,
+}
+
+impl RobotsError {
+    /// Returns the category of the error, so a scheduler can decide between
+    /// retrying and dropping a URL without matching on messages.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            RobotsError::NullMatcher => ErrorCategory::Transient,
+            RobotsError::UrlTooLong { .. } => ErrorCategory::PolicyDenied,
+            RobotsError::UnsupportedScheme { .. }
+            | RobotsError::Unsupported { .. }
+            | RobotsError::LibraryLoad { .. }
+            | RobotsError::MissingSymbol { .. }
+            | RobotsError::InteriorNul { .. } => ErrorCategory::Permanent,
+        }
+    }
+}
+
+impl fmt::Display for RobotsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RobotsError::UrlTooLong { len, max } => {
+                write!(f, "URL is {len} bytes, longer than the {max} byte limit")
+            }
+            RobotsError::UnsupportedScheme { scheme } => {
+                write!(
+                    f,
+                    "unsupported URL scheme \"{scheme}\", expected http or https"
+                )
+            }
+            RobotsError::Unsupported { symbol } => {
+                write!(
+                    f,
+                    "the loaded robots library does not provide {symbol}; it is older than this binding"
+                )
+            }
+            RobotsError::LibraryLoad { path, message } => {
+                write!(f, "failed to load robots library {path}: {message}")
+            }
+            RobotsError::MissingSymbol { symbol } => {
+                write!(f, "robots library is missing required function {symbol}")
+            }
+            RobotsError::InteriorNul { field } => write!(f, "{field} contains a NUL byte"),
+            RobotsError::NullMatcher => write!(f, "failed to allocate the robots matcher"),
+        }
+    }
+}
+
+impl std::error::Error for RobotsError {}
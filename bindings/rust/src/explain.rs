@@ -0,0 +1,259 @@
+//! Step-by-step account of a single matching decision.
+//!
+//! [`explain`] lists every Allow and Disallow rule the matcher considered
+//! for an agent and URL, how much of the path each one matched, and why the
+//! deciding rule won.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::explain::{explain, Reason};
+//!
+//! let robots_txt = "User-agent: *\nDisallow: /shop\nAllow: /shop/public\n";
+//! let explanation = explain(robots_txt, "Googlebot", "https://example.com/shop/public/a");
+//! assert!(explanation.allowed);
+//! assert_eq!(explanation.reason, Reason::LongestMatch);
+//! assert_eq!(explanation.deciding_rule().unwrap().rule.line, 3);
+//! println!("{explanation}");
+//! ```
+
+use std::fmt;
+use std::os::raw::c_char;
+
+use crate::model::{self, Rule, Verb};
+use crate::{symbols, RobotsMatcher};
+
+#[repr(C)]
+pub(crate) struct RawTraceRule {
+    kind: i32,
+    line: i32,
+    pattern: *const c_char,
+    pattern_len: usize,
+    priority: i32,
+    specific: bool,
+    superseded: bool,
+}
+
+/// A rule the matcher considered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub rule: Rule,
+    /// Match priority, the length of the matched pattern, or None if the
+    /// pattern does not match the path.
+    pub priority: Option<i32>,
+    /// True if the rule is in a group naming the agent, false if it is in
+    /// the `*` group.
+    pub specific: bool,
+    /// False for rules that cannot decide: `*` rules when a group names the
+    /// agent, and rules of a group dropped for a more specific agent.
+    pub applies: bool,
+}
+
+/// Why the decision came out the way it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reason {
+    /// No applicable rule matched a non-empty part of the path, so the URL
+    /// is allowed.
+    NoMatch,
+    /// The deciding rule has a longer match than any rule of the other verb.
+    LongestMatch,
+    /// An Allow and a Disallow matched with the same priority; Allow wins.
+    AllowWinsTie,
+}
+
+/// The decision of [`explain`] and the rules behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub allowed: bool,
+    /// True if a group names the agent, so the `*` group is ignored.
+    pub specific_group: bool,
+    /// Allow and Disallow rules of the groups for the agent or `*`, in file
+    /// order. Empty with a library too old to trace checks.
+    pub candidates: Vec<Candidate>,
+    /// Index in `candidates` of the deciding rule, or None for
+    /// [`Reason::NoMatch`].
+    pub winner: Option<usize>,
+    pub reason: Reason,
+}
+
+impl Explanation {
+    /// Returns the rule that decided, if any.
+    pub fn deciding_rule(&self) -> Option<&Candidate> {
+        self.winner.map(|i| &self.candidates[i])
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decision = if self.allowed {
+            "allowed"
+        } else {
+            "disallowed"
+        };
+        match (self.deciding_rule(), self.reason) {
+            (None, _) | (_, Reason::NoMatch) => writeln!(f, "{decision}: no rule matched")?,
+            (Some(winner), Reason::LongestMatch) => {
+                writeln!(f, "{decision} by line {}: longest match", winner.rule.line)?
+            }
+            (Some(winner), Reason::AllowWinsTie) => writeln!(
+                f,
+                "{decision} by line {}: Allow wins a tie",
+                winner.rule.line
+            )?,
+        }
+        for candidate in &self.candidates {
+            let verb = match candidate.rule.verb {
+                Verb::Allow => "Allow",
+                Verb::Disallow => "Disallow",
+            };
+            write!(
+                f,
+                "  line {}: {verb}: {} ",
+                candidate.rule.line, candidate.rule.pattern
+            )?;
+            match candidate.priority {
+                Some(priority) => write!(f, "(matched, priority {priority})")?,
+                None => write!(f, "(no match)")?,
+            }
+            if !candidate.applies {
+                write!(f, ", ignored")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks `url` for `user_agent` against `robots_txt` and explains the
+/// decision.
+pub fn explain(robots_txt: &str, user_agent: &str, url: &str) -> Explanation {
+    let matcher = RobotsMatcher::new();
+    let tracing = match symbols::robots_set_tracing() {
+        Some(set_tracing) => {
+            unsafe { set_tracing(matcher.raw(), true) };
+            true
+        }
+        None => false,
+    };
+    let result = matcher.check(robots_txt, user_agent, url);
+    let specific_group = matcher.ever_seen_specific_agent();
+    let candidates = if tracing {
+        traced_rules(&matcher, specific_group)
+    } else {
+        Vec::new()
+    };
+
+    let best = |verb: Verb| {
+        candidates
+            .iter()
+            .filter(|c| c.applies && c.rule.verb == verb)
+            .filter_map(|c| c.priority)
+            .max()
+            .unwrap_or(-1)
+    };
+    let (allow, disallow) = (best(Verb::Allow), best(Verb::Disallow));
+    let reason = if candidates.is_empty() {
+        match result.line {
+            0 => Reason::NoMatch,
+            _ => Reason::LongestMatch,
+        }
+    } else if allow <= 0 && disallow <= 0 {
+        Reason::NoMatch
+    } else if allow == disallow {
+        Reason::AllowWinsTie
+    } else {
+        Reason::LongestMatch
+    };
+    let winner = match reason {
+        Reason::NoMatch => None,
+        _ => candidates
+            .iter()
+            .position(|c| c.applies && c.rule.line == result.line),
+    };
+    Explanation {
+        allowed: result.allowed,
+        specific_group,
+        candidates,
+        winner,
+        reason,
+    }
+}
+
+fn traced_rules(matcher: &RobotsMatcher, specific_group: bool) -> Vec<Candidate> {
+    let (Some(count), Some(get_rule)) = (
+        symbols::robots_trace_rule_count(),
+        symbols::robots_get_trace_rule(),
+    ) else {
+        return Vec::new();
+    };
+    let mut candidates = Vec::new();
+    for i in 0..unsafe { count(matcher.raw()) } {
+        let mut raw = RawTraceRule {
+            kind: 0,
+            line: 0,
+            pattern: std::ptr::null(),
+            pattern_len: 0,
+            priority: -1,
+            specific: false,
+            superseded: false,
+        };
+        if !unsafe { get_rule(matcher.raw(), i, &mut raw) } {
+            break;
+        }
+        // SAFETY: the pattern is owned by the matcher and valid until the
+        // next check.
+        let pattern =
+            unsafe { std::slice::from_raw_parts(raw.pattern.cast::<u8>(), raw.pattern_len) };
+        candidates.push(Candidate {
+            rule: Rule {
+                verb: if raw.kind == model::ALLOW {
+                    Verb::Allow
+                } else {
+                    Verb::Disallow
+                },
+                pattern: String::from_utf8_lossy(pattern).into_owned(),
+                line: raw.line,
+            },
+            priority: (raw.priority >= 0).then_some(raw.priority),
+            specific: raw.specific,
+            applies: raw.specific == specific_group && !raw.superseded,
+        });
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain() {
+        let robots = "User-agent: *\nDisallow: /shop\nAllow: /shop/public\n\n\
+                      User-agent: Foo\nDisallow: /\n\n\
+                      User-agent: FooBot\nDisallow: /a\nAllow: /a\nDisallow: /b/\n";
+        let explanation = explain(robots, "BarBot", "http://x.com/shop/public/1");
+        assert!(explanation.allowed);
+        assert!(!explanation.specific_group);
+        assert_eq!(explanation.reason, Reason::LongestMatch);
+        assert_eq!(explanation.candidates.len(), 2);
+        assert_eq!(explanation.candidates[0].priority, Some(5));
+        assert_eq!(explanation.deciding_rule().unwrap().rule.line, 3);
+
+        let explanation = explain(robots, "FooBot", "http://x.com/a/1");
+        assert!(explanation.allowed);
+        assert!(explanation.specific_group);
+        assert_eq!(explanation.reason, Reason::AllowWinsTie);
+        assert_eq!(explanation.deciding_rule().unwrap().rule.line, 10);
+        let applies: Vec<_> = explanation.candidates.iter().map(|c| c.applies).collect();
+        // The `*` rules, then FooBot's; Foo's group is not for FooBot.
+        assert_eq!(applies, [false, false, true, true, true]);
+        assert_eq!(explanation.candidates[4].priority, None);
+
+        let explanation = explain(robots, "FooBot", "http://x.com/shop");
+        assert_eq!(explanation.reason, Reason::NoMatch);
+        assert_eq!(explanation.winner, None);
+        assert!(explanation
+            .to_string()
+            .starts_with("allowed: no rule matched\n"));
+    }
+}
@@ -0,0 +1,104 @@
+//! Safety checks for robots.txt files before they are deployed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::guard::{assert_not_blocking_everything, MAJOR_CRAWLERS};
+//!
+//! let robots_txt = "User-agent: *\nDisallow: /\n";
+//! if let Err(findings) = assert_not_blocking_everything(robots_txt, MAJOR_CRAWLERS) {
+//!     for f in &findings {
+//!         eprintln!("{} is blocked from the whole site (line {})", f.user_agent, f.line);
+//!     }
+//!     std::process::exit(1);
+//! }
+//! ```
+
+use crate::RobotsMatcher;
+
+/// User-agents of the major search crawlers, suitable as `critical_agents`.
+pub const MAJOR_CRAWLERS: &[&str] = &["Googlebot", "Bingbot", "DuckDuckBot", "Applebot"];
+
+/// A path no real site is expected to list in its robots.txt, checked in
+/// place of the pages of the site.
+const PROBE_PATH: &str = "/robots-guard-probe/x";
+
+/// A critical user-agent that the robots.txt blocks from the whole site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockingFinding {
+    /// The user-agent that is blocked.
+    pub user_agent: String,
+    /// Line number of the rule that blocks the site.
+    pub line: i32,
+    /// True if the rule comes from a group naming this agent, false if it
+    /// is inherited from the `*` group.
+    pub specific: bool,
+}
+
+/// Checks that none of `critical_agents` is disallowed from the whole site.
+///
+/// An agent counts as blocked when an arbitrary page below the root is
+/// disallowed for it, which covers `Disallow: /`, `Disallow: /*` and their
+/// typo variants, also with `Allow: /$` keeping only the homepage open. A
+/// rule for the homepage alone, such as `Disallow: /$`, does not block the
+/// site. Returns one finding per blocked agent, in the order the agents
+/// were given.
+pub fn assert_not_blocking_everything(
+    robots_txt: &str,
+    critical_agents: &[&str],
+) -> Result<(), Vec<BlockingFinding>> {
+    let matcher = RobotsMatcher::new();
+    let findings: Vec<BlockingFinding> = critical_agents
+        .iter()
+        .filter(|agent| !matcher.is_allowed(robots_txt, agent, PROBE_PATH))
+        .map(|agent| BlockingFinding {
+            user_agent: (*agent).to_string(),
+            line: matcher.matching_line(),
+            specific: matcher.ever_seen_specific_agent(),
+        })
+        .collect();
+
+    if findings.is_empty() {
+        Ok(())
+    } else {
+        Err(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_block_is_reported_for_every_agent() {
+        let robots = "User-agent: *\nDisallow: /\n";
+        let findings =
+            assert_not_blocking_everything(robots, &["Googlebot", "Bingbot"]).unwrap_err();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].user_agent, "Googlebot");
+        assert_eq!(findings[0].line, 2);
+        assert!(!findings[0].specific);
+    }
+
+    #[test]
+    fn test_specific_block_and_partial_disallow() {
+        let robots = "User-agent: Bingbot\nDisallow: /\n\nUser-agent: *\nDisallow: /admin/\n";
+        let findings = assert_not_blocking_everything(robots, MAJOR_CRAWLERS).unwrap_err();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].user_agent, "Bingbot");
+        assert!(findings[0].specific);
+
+        let robots = "User-agent: *\nDisallow: /admin/\n";
+        assert!(assert_not_blocking_everything(robots, MAJOR_CRAWLERS).is_ok());
+    }
+
+    #[test]
+    fn test_homepage_rules() {
+        let robots = "User-agent: *\nAllow: /$\nDisallow: /\n";
+        let findings = assert_not_blocking_everything(robots, &["Googlebot"]).unwrap_err();
+        assert_eq!(findings[0].line, 3);
+
+        let robots = "User-agent: *\nDisallow: /$\n";
+        assert!(assert_not_blocking_everything(robots, MAJOR_CRAWLERS).is_ok());
+    }
+}
@@ -0,0 +1,371 @@
+//! Parser callbacks, the Rust side of upstream's `RobotsParseHandler`.
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::ffi::c_void;
+use std::os::raw::{c_char, c_double, c_int};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{symbols, ContentSignal, RequestRate, RobotsError};
+
+/// Receives the directives of a robots.txt from [`parse_robots_txt`], in
+/// file order. Every method does nothing by default.
+///
+/// Values are passed as the parser reports them: trimmed, without the
+/// comment, and with Allow/Disallow patterns percent-escaped.
+///
+/// ```no_run
+/// use robotstxt::{parse_robots_txt, ParseHandler};
+///
+/// #[derive(Default)]
+/// struct Hosts(Vec<String>);
+///
+/// impl ParseHandler for Hosts {
+///     fn handle_unknown_action(&mut self, _line: i32, action: &str, value: &str) {
+///         if action.eq_ignore_ascii_case("host") {
+///             self.0.push(value.to_string());
+///         }
+///     }
+/// }
+///
+/// let mut hosts = Hosts::default();
+/// parse_robots_txt("User-agent: *\nHost: example.com\n", &mut hosts).unwrap();
+/// assert_eq!(hosts.0, ["example.com"]);
+/// ```
+#[allow(unused_variables)]
+pub trait ParseHandler {
+    fn handle_robots_start(&mut self) {}
+    fn handle_robots_end(&mut self) {}
+    fn handle_user_agent(&mut self, line: i32, value: &str) {}
+    fn handle_allow(&mut self, line: i32, value: &str) {}
+    fn handle_disallow(&mut self, line: i32, value: &str) {}
+    fn handle_sitemap(&mut self, line: i32, value: &str) {}
+    fn handle_crawl_delay(&mut self, line: i32, seconds: f64) {}
+    fn handle_request_rate(&mut self, line: i32, rate: RequestRate) {}
+    /// Only called if Content-Signal support is compiled in; see
+    /// [`content_signal_supported`](crate::content_signal_supported).
+    fn handle_content_signal(&mut self, line: i32, signal: ContentSignal) {}
+    fn handle_unknown_action(&mut self, line: i32, action: &str, value: &str) {}
+}
+
+/// Parses `robots_txt` and reports each directive to `handler`.
+///
+/// Fails with [`RobotsError::Unsupported`] if the library has no
+/// `robots_parse`. A panic in `handler` stops further callbacks and is
+/// resumed once the parser returns.
+pub fn parse_robots_txt<H: ParseHandler>(
+    robots_txt: &str,
+    handler: &mut H,
+) -> Result<(), RobotsError> {
+    let parse = symbols::robots_parse().ok_or(RobotsError::Unsupported {
+        symbol: "robots_parse",
+    })?;
+    let callbacks = RawCallbacks {
+        robots_start: robots_start::<H>,
+        robots_end: robots_end::<H>,
+        user_agent: user_agent::<H>,
+        allow: allow::<H>,
+        disallow: disallow::<H>,
+        sitemap: sitemap::<H>,
+        crawl_delay: crawl_delay::<H>,
+        request_rate: request_rate::<H>,
+        content_signal: content_signal::<H>,
+        unknown_action: unknown_action::<H>,
+    };
+    let mut context = Context {
+        handler,
+        panic: None,
+    };
+    unsafe {
+        parse(
+            robots_txt.as_ptr().cast(),
+            robots_txt.len(),
+            &callbacks,
+            (&mut context as *mut Context<H>).cast(),
+        );
+    }
+    if let Some(payload) = context.panic {
+        panic::resume_unwind(payload);
+    }
+    Ok(())
+}
+
+/// A line whose key the parser does not recognize, such as `Host:`,
+/// `Clean-param:` or a vendor extension. Matching ignores these lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnknownDirective {
+    pub line: i32,
+    /// The key as written, without the colon.
+    pub key: String,
+    pub value: String,
+}
+
+/// A [`ParseHandler`] that collects the [`UnknownDirective`]s of a
+/// robots.txt, in file order.
+///
+/// Lines without a key and value, such as a bare word, are not directives
+/// and are not reported; [`lint`](crate::lint::lint) flags them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnknownDirectives(pub Vec<UnknownDirective>);
+
+impl ParseHandler for UnknownDirectives {
+    fn handle_unknown_action(&mut self, line: i32, action: &str, value: &str) {
+        self.0.push(UnknownDirective {
+            line,
+            key: action.to_string(),
+            value: value.to_string(),
+        });
+    }
+}
+
+/// Returns the directives of `robots_txt` the parser ignores, in file
+/// order.
+///
+/// Fails with [`RobotsError::Unsupported`] if the library has no
+/// `robots_parse`.
+pub fn unknown_directives(robots_txt: &str) -> Result<Vec<UnknownDirective>, RobotsError> {
+    let mut collector = UnknownDirectives::default();
+    parse_robots_txt(robots_txt, &mut collector)?;
+    Ok(collector.0)
+}
+
+#[repr(C)]
+pub(crate) struct RawCallbacks {
+    robots_start: unsafe extern "C" fn(*mut c_void),
+    robots_end: unsafe extern "C" fn(*mut c_void),
+    user_agent: unsafe extern "C" fn(*mut c_void, c_int, *const c_char, usize),
+    allow: unsafe extern "C" fn(*mut c_void, c_int, *const c_char, usize),
+    disallow: unsafe extern "C" fn(*mut c_void, c_int, *const c_char, usize),
+    sitemap: unsafe extern "C" fn(*mut c_void, c_int, *const c_char, usize),
+    crawl_delay: unsafe extern "C" fn(*mut c_void, c_int, c_double),
+    request_rate: unsafe extern "C" fn(*mut c_void, c_int, *const RequestRate),
+    content_signal: unsafe extern "C" fn(*mut c_void, c_int, *const ContentSignal),
+    unknown_action:
+        unsafe extern "C" fn(*mut c_void, c_int, *const c_char, usize, *const c_char, usize),
+}
+
+struct Context<'a, H> {
+    handler: &'a mut H,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+/// Runs `f` on the handler behind `context` unless an earlier callback
+/// panicked. Unwinding into the C++ parser is undefined behavior.
+unsafe fn with_handler<H: ParseHandler>(context: *mut c_void, f: impl FnOnce(&mut H)) {
+    let context = &mut *context.cast::<Context<H>>();
+    if context.panic.is_some() {
+        return;
+    }
+    let handler = &mut *context.handler;
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(handler))) {
+        context.panic = Some(payload);
+    }
+}
+
+/// Borrows a parser string. Long lines are cut at a byte limit, so a value
+/// may end in the middle of a character.
+unsafe fn text<'a>(ptr: *const c_char, len: usize) -> Cow<'a, str> {
+    if ptr.is_null() {
+        return Cow::Borrowed("");
+    }
+    String::from_utf8_lossy(std::slice::from_raw_parts(ptr.cast::<u8>(), len))
+}
+
+unsafe extern "C" fn robots_start<H: ParseHandler>(context: *mut c_void) {
+    with_handler(context, |h: &mut H| h.handle_robots_start());
+}
+
+unsafe extern "C" fn robots_end<H: ParseHandler>(context: *mut c_void) {
+    with_handler(context, |h: &mut H| h.handle_robots_end());
+}
+
+unsafe extern "C" fn user_agent<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    value: *const c_char,
+    len: usize,
+) {
+    with_handler(context, |h: &mut H| {
+        h.handle_user_agent(line, &text(value, len))
+    });
+}
+
+unsafe extern "C" fn allow<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    value: *const c_char,
+    len: usize,
+) {
+    with_handler(context, |h: &mut H| h.handle_allow(line, &text(value, len)));
+}
+
+unsafe extern "C" fn disallow<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    value: *const c_char,
+    len: usize,
+) {
+    with_handler(context, |h: &mut H| {
+        h.handle_disallow(line, &text(value, len))
+    });
+}
+
+unsafe extern "C" fn sitemap<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    value: *const c_char,
+    len: usize,
+) {
+    with_handler(context, |h: &mut H| {
+        h.handle_sitemap(line, &text(value, len))
+    });
+}
+
+unsafe extern "C" fn crawl_delay<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    seconds: c_double,
+) {
+    with_handler(context, |h: &mut H| h.handle_crawl_delay(line, seconds));
+}
+
+unsafe extern "C" fn request_rate<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    rate: *const RequestRate,
+) {
+    let rate = *rate;
+    with_handler(context, |h: &mut H| h.handle_request_rate(line, rate));
+}
+
+unsafe extern "C" fn content_signal<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    signal: *const ContentSignal,
+) {
+    let signal = *signal;
+    with_handler(context, |h: &mut H| h.handle_content_signal(line, signal));
+}
+
+unsafe extern "C" fn unknown_action<H: ParseHandler>(
+    context: *mut c_void,
+    line: c_int,
+    action: *const c_char,
+    action_len: usize,
+    value: *const c_char,
+    value_len: usize,
+) {
+    with_handler(context, |h: &mut H| {
+        h.handle_unknown_action(line, &text(action, action_len), &text(value, value_len))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl ParseHandler for Recorder {
+        fn handle_robots_start(&mut self) {
+            self.0.push("start".into());
+        }
+        fn handle_robots_end(&mut self) {
+            self.0.push("end".into());
+        }
+        fn handle_user_agent(&mut self, line: i32, value: &str) {
+            self.0.push(format!("{line} user-agent {value}"));
+        }
+        fn handle_allow(&mut self, line: i32, value: &str) {
+            self.0.push(format!("{line} allow {value}"));
+        }
+        fn handle_disallow(&mut self, line: i32, value: &str) {
+            self.0.push(format!("{line} disallow {value}"));
+        }
+        fn handle_sitemap(&mut self, line: i32, value: &str) {
+            self.0.push(format!("{line} sitemap {value}"));
+        }
+        fn handle_crawl_delay(&mut self, line: i32, seconds: f64) {
+            self.0.push(format!("{line} crawl-delay {seconds}"));
+        }
+        fn handle_request_rate(&mut self, line: i32, rate: RequestRate) {
+            self.0.push(format!(
+                "{line} request-rate {}/{}",
+                rate.requests, rate.seconds
+            ));
+        }
+        fn handle_unknown_action(&mut self, line: i32, action: &str, value: &str) {
+            self.0.push(format!("{line} {action} {value}"));
+        }
+    }
+
+    #[test]
+    fn test_parse_robots_txt() {
+        if crate::missing_symbols().contains(&"robots_parse") {
+            return;
+        }
+        let robots = "User-agent: bot # comment\nDisallow: /caf\u{e9}\nAllow: /a\n\
+                      Crawl-delay: 1.5\nRequest-rate: 1/5\nHost: example.com\n\
+                      Sitemap: https://example.com/s.xml\n";
+        let mut recorder = Recorder::default();
+        parse_robots_txt(robots, &mut recorder).unwrap();
+        assert_eq!(
+            recorder.0,
+            [
+                "start",
+                "1 user-agent bot",
+                "2 disallow /caf%C3%A9",
+                "3 allow /a",
+                "4 crawl-delay 1.5",
+                "5 request-rate 1/5",
+                "6 Host example.com",
+                "7 sitemap https://example.com/s.xml",
+                "end",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_directives() {
+        if crate::missing_symbols().contains(&"robots_parse") {
+            return;
+        }
+        let robots = "Host: example.com\nUser-agent: *\nDisallow: /a\n\
+                      Clean-param: ref /shop # tracking\nX-Vendor: on\nnonsense\n";
+        let found = unknown_directives(robots).unwrap();
+        let found: Vec<_> = found
+            .iter()
+            .map(|d| (d.line, d.key.as_str(), d.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (1, "Host", "example.com"),
+                (4, "Clean-param", "ref /shop"),
+                (5, "X-Vendor", "on"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handler_panic_is_resumed() {
+        if crate::missing_symbols().contains(&"robots_parse") {
+            return;
+        }
+        struct Panics(usize);
+        impl ParseHandler for Panics {
+            fn handle_disallow(&mut self, _line: i32, _value: &str) {
+                self.0 += 1;
+                panic!("handler failed");
+            }
+        }
+        let mut handler = Panics(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            parse_robots_txt("User-agent: *\nDisallow: /a\nDisallow: /b\n", &mut handler)
+        }));
+        assert!(result.is_err());
+        assert_eq!(handler.0, 1);
+    }
+}
@@ -0,0 +1,158 @@
+//! Standalone HTML pages for audit reports.
+//!
+//! Each function renders a complete page with inline styles and no external
+//! assets, so the output can be saved to a file and shared as-is.
+//!
+//! Requires the `html` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::audit::audit_consistency;
+//! use robotstxt::html;
+//!
+//! let robots_txt = "User-agent: *\nDisallow: /private/\n";
+//! let conflicts = audit_consistency(robots_txt, ["https://example.com/private/a"]);
+//! std::fs::write("conflicts.html", html::sitemap_conflicts(&conflicts)).unwrap();
+//! ```
+
+use std::fmt::Write;
+
+use crate::audit::{ImpactReport, IndexabilityFinding, IndexabilityIssue, SitemapConflict};
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse}th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+th{background:#f4f4f4}td.num{text-align:right}p.empty{color:#666}";
+
+/// Renders an [`ImpactReport`] as a table of rules ranked by hits.
+pub fn impact_report(report: &ImpactReport) -> String {
+    let rows = report.rules.iter().map(|r| {
+        let decision = if r.allows { "allow" } else { "disallow" };
+        vec![
+            Cell::Num(r.line.to_string()),
+            Cell::Text(decision.to_string()),
+            Cell::Num(r.urls.to_string()),
+            Cell::Num(r.hits.to_string()),
+        ]
+    });
+    let mut body = table(&["Line", "Decision", "URLs", "Hits"], rows);
+    let _ = write!(
+        body,
+        "<p>{} URLs ({} hits) matched no rule.</p>",
+        report.unmatched_urls, report.unmatched_hits
+    );
+    page("Rule impact", &body)
+}
+
+/// Renders the result of [`audit_consistency`](crate::audit::audit_consistency).
+pub fn sitemap_conflicts(conflicts: &[SitemapConflict]) -> String {
+    let rows = conflicts.iter().map(|c| {
+        vec![
+            Cell::Text(c.url.clone()),
+            Cell::Text(c.user_agent.clone()),
+            Cell::Num(c.line.to_string()),
+        ]
+    });
+    page(
+        "Sitemap URLs blocked by robots.txt",
+        &table(&["URL", "User-agent", "Line"], rows),
+    )
+}
+
+/// Renders the result of [`audit_indexability`](crate::audit::audit_indexability).
+pub fn indexability_findings(findings: &[IndexabilityFinding]) -> String {
+    let rows = findings.iter().map(|f| {
+        let issue = match f.issue {
+            IndexabilityIssue::NoindexNotSeen => "Blocked, so noindex is never seen",
+            IndexabilityIssue::IndexedWithoutContent => "Blocked, may be indexed without content",
+            IndexabilityIssue::ConflictingDirectives => "X-Robots-Tag and meta robots disagree",
+        };
+        vec![
+            Cell::Text(f.url.clone()),
+            Cell::Text(issue.to_string()),
+            Cell::Num(f.line.map(|l| l.to_string()).unwrap_or_default()),
+        ]
+    });
+    page(
+        "Indexability contradictions",
+        &table(&["URL", "Issue", "Line"], rows),
+    )
+}
+
+enum Cell {
+    Text(String),
+    Num(String),
+}
+
+fn table<I>(headers: &[&str], rows: I) -> String
+where
+    I: IntoIterator<Item = Vec<Cell>>,
+{
+    let mut html = String::from("<table><tr>");
+    for header in headers {
+        let _ = write!(html, "<th>{}</th>", escape(header));
+    }
+    html.push_str("</tr>");
+    let mut empty = true;
+    for row in rows {
+        empty = false;
+        html.push_str("<tr>");
+        for cell in row {
+            let _ = match cell {
+                Cell::Text(text) => write!(html, "<td>{}</td>", escape(&text)),
+                Cell::Num(num) => write!(html, "<td class=\"num\">{}</td>", escape(&num)),
+            };
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+    if empty {
+        return "<p class=\"empty\">No findings.</p>".to_string();
+    }
+    html
+}
+
+fn page(title: &str, body: &str) -> String {
+    let title = escape(title);
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n{body}\n</body>\n</html>\n"
+    )
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sitemap_conflicts_page() {
+        let conflicts = [SitemapConflict {
+            url: "https://example.com/a?x=1&y=<2>".to_string(),
+            user_agent: "Googlebot".to_string(),
+            line: 3,
+        }];
+        let html = sitemap_conflicts(&conflicts);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<td>https://example.com/a?x=1&amp;y=&lt;2&gt;</td>"));
+        assert!(html.contains("<td class=\"num\">3</td>"));
+        assert!(!html.contains("http-equiv") && !html.contains("<link") && !html.contains("src="));
+
+        assert!(sitemap_conflicts(&[]).contains("No findings."));
+    }
+}
@@ -11,8 +11,52 @@
 //! println!("Access: {}", if allowed { "allowed" } else { "disallowed" });
 //! ```
 
-use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_double, c_int, c_void};
+use std::cell::Cell;
+use std::ffi::CStr;
+#[cfg(not(feature = "dlopen"))]
+use std::os::raw::c_double;
+use std::os::raw::{c_char, c_int};
+use std::ptr::NonNull;
+use std::time::{Duration, Instant};
+
+pub mod audit;
+pub mod builder;
+pub mod cache;
+pub mod clock;
+pub mod compat;
+#[cfg(feature = "rayon")]
+pub mod corpus;
+#[cfg(feature = "dlopen")]
+pub mod dynamic;
+mod error;
+pub mod explain;
+pub mod guard;
+mod handler;
+#[cfg(feature = "html")]
+pub mod html;
+pub mod lint;
+pub mod model;
+pub mod normalize;
+pub mod policy_tests;
+pub mod scheduler;
+pub mod security;
+mod shared;
+pub mod simulate;
+mod symbols;
+pub mod testvectors;
+pub mod watermark;
+
+pub use compat::{DefaultMatcher, Robot};
+pub use error::{ErrorCategory, RobotsError};
+pub use handler::{
+    parse_robots_txt, unknown_directives, ParseHandler, UnknownDirective, UnknownDirectives,
+};
+pub use shared::{MatcherPool, SharedMatcher};
+
+// With `dlopen`, the core functions are wrappers that call into the
+// runtime-loaded library instead of the extern block below.
+#[cfg(feature = "dlopen")]
+use dynamic::*;
 
 // FFI declarations
 #[repr(C)]
@@ -20,6 +64,11 @@ struct RobotsMatcherOpaque {
     _private: [u8; 0],
 }
 
+#[repr(C)]
+struct RobotsTxtOpaque {
+    _private: [u8; 0],
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct RequestRate {
@@ -27,6 +76,16 @@ pub struct RequestRate {
     pub seconds: c_int,
 }
 
+impl RequestRate {
+    /// Returns the seconds to wait between requests, or None if
+    /// `requests` is not positive.
+    pub fn interval_secs(&self) -> Option<f64> {
+        (self.requests > 0).then(|| f64::from(self.seconds) / f64::from(self.requests))
+    }
+}
+
+/// Content-signal values as the C API reports them: -1 = unset, 0 = no,
+/// 1 = yes. [`ContentSignalPolicy`] is the same with a [`Signal`] per field.
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct ContentSignal {
@@ -35,6 +94,189 @@ pub struct ContentSignal {
     pub search: i8,
 }
 
+/// A single content-signal preference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Signal {
+    Yes,
+    No,
+    #[default]
+    Unset,
+}
+
+impl Signal {
+    /// Returns false only for an explicit `no`; unset means allowed.
+    pub fn allows(self) -> bool {
+        self != Signal::No
+    }
+}
+
+impl From<i8> for Signal {
+    fn from(value: i8) -> Self {
+        match value {
+            1 => Signal::Yes,
+            0 => Signal::No,
+            _ => Signal::Unset,
+        }
+    }
+}
+
+impl From<Signal> for i8 {
+    fn from(signal: Signal) -> Self {
+        match signal {
+            Signal::Yes => 1,
+            Signal::No => 0,
+            Signal::Unset => -1,
+        }
+    }
+}
+
+/// Content-signal preferences of a robots.txt group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentSignalPolicy {
+    /// Training or fine-tuning AI models.
+    pub ai_train: Signal,
+    /// Using content in AI models for real-time generation.
+    pub ai_input: Signal,
+    /// Building search indexes and providing results.
+    pub search: Signal,
+}
+
+impl From<ContentSignal> for ContentSignalPolicy {
+    fn from(signal: ContentSignal) -> Self {
+        ContentSignalPolicy {
+            ai_train: signal.ai_train.into(),
+            ai_input: signal.ai_input.into(),
+            search: signal.search.into(),
+        }
+    }
+}
+
+impl From<ContentSignalPolicy> for ContentSignal {
+    fn from(policy: ContentSignalPolicy) -> Self {
+        ContentSignal {
+            ai_train: policy.ai_train.into(),
+            ai_input: policy.ai_input.into(),
+            search: policy.search.into(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct RawTiming {
+    url_parse_ns: u64,
+    group_selection_ns: u64,
+    rule_matching_ns: u64,
+    total_ns: u64,
+}
+
+/// Time spent in each phase of a single check, see
+/// [`RobotsMatcher::set_profiling`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecisionTiming {
+    /// Extracting the path from the URL.
+    pub url_parse: Duration,
+    /// Splitting robots.txt into lines and directives.
+    pub parsing: Duration,
+    /// Handling user-agent lines.
+    pub group_selection: Duration,
+    /// Matching Allow/Disallow patterns against the path.
+    pub rule_matching: Duration,
+    /// Marshaling arguments and crossing the FFI boundary.
+    pub ffi_overhead: Duration,
+}
+
+impl DecisionTiming {
+    /// Returns the sum of all phases.
+    pub fn total(&self) -> Duration {
+        self.url_parse
+            + self.parsing
+            + self.group_selection
+            + self.rule_matching
+            + self.ffi_overhead
+    }
+}
+
+/// The kind of rule that decided a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MatchDirective {
+    Allow,
+    Disallow,
+    /// No rule matched and the URL is allowed by default.
+    Default,
+}
+
+/// The decision of [`RobotsMatcher::check`] and the rule behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchResult {
+    pub allowed: bool,
+    /// Line of the deciding rule, or 0 if none matched.
+    pub line: i32,
+    pub directive: MatchDirective,
+    /// The deciding rule's pattern as the matcher sees it: percent-escaped,
+    /// with the original `*` and `$` wildcards. None if no rule matched or
+    /// the library is too old to report it.
+    pub pattern: Option<String>,
+}
+
+/// What to do with URLs longer than the limit set by
+/// [`RobotsMatcher::set_url_limit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlLengthPolicy {
+    /// Check only the first `max_len` bytes of the URL, cut back to a
+    /// character boundary.
+    Truncate,
+    /// Refuse to check the URL. `try_is_allowed` returns
+    /// [`RobotsError::UrlTooLong`] and `is_allowed` returns false.
+    Reject,
+}
+
+/// Maximum URL length accepted by a matcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UrlLimit {
+    pub max_len: usize,
+    pub policy: UrlLengthPolicy,
+}
+
+/// Counters describing how the parser treated each line of a robots.txt.
+///
+/// Unknown, malformed and out-of-group lines are silently skipped when
+/// matching; these counters make that visible.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    pub total_lines: c_int,
+    pub directives: c_int,
+    pub unknown_directives: c_int,
+    pub malformed_lines: c_int,
+    pub rules_outside_groups: c_int,
+    pub typo_directives: c_int,
+    pub truncated_lines: c_int,
+    /// Lines with invalid UTF-8, or U+FFFD left by lossy decoding.
+    pub invalid_utf8_lines: c_int,
+    /// Lines with zero-width or bidi control characters, which make a
+    /// pattern silently unmatchable.
+    pub invisible_char_lines: c_int,
+    /// User-agent lines whose value starts with a character no crawler name
+    /// can match, e.g. an emoji.
+    pub unmatchable_user_agents: c_int,
+}
+
+impl ParseStats {
+    /// Returns the number of lines that had no effect on matching because
+    /// they were unknown, malformed or outside any user-agent group.
+    pub fn ignored_lines(&self) -> c_int {
+        self.unknown_directives + self.malformed_lines + self.rules_outside_groups
+    }
+}
+
+#[cfg(not(feature = "dlopen"))]
 extern "C" {
     fn robots_matcher_create() -> *mut RobotsMatcherOpaque;
     fn robots_matcher_free(matcher: *mut RobotsMatcherOpaque);
@@ -49,6 +291,17 @@ extern "C" {
         url_len: usize,
     ) -> bool;
 
+    fn robots_allowed_by_robots_multi(
+        matcher: *mut RobotsMatcherOpaque,
+        robots_txt: *const c_char,
+        robots_txt_len: usize,
+        user_agents: *const *const c_char,
+        user_agent_lens: *const usize,
+        num_user_agents: usize,
+        url: *const c_char,
+        url_len: usize,
+    ) -> bool;
+
     fn robots_matching_line(matcher: *const RobotsMatcherOpaque) -> c_int;
     fn robots_ever_seen_specific_agent(matcher: *const RobotsMatcherOpaque) -> bool;
 
@@ -61,16 +314,6 @@ extern "C" {
         rate: *mut RequestRate,
     ) -> bool;
 
-    fn robots_content_signal_supported() -> bool;
-    fn robots_has_content_signal(matcher: *const RobotsMatcherOpaque) -> bool;
-    fn robots_get_content_signal(
-        matcher: *const RobotsMatcherOpaque,
-        signal: *mut ContentSignal,
-    ) -> bool;
-    fn robots_allows_ai_train(matcher: *const RobotsMatcherOpaque) -> bool;
-    fn robots_allows_ai_input(matcher: *const RobotsMatcherOpaque) -> bool;
-    fn robots_allows_search(matcher: *const RobotsMatcherOpaque) -> bool;
-
     fn robots_is_valid_user_agent(user_agent: *const c_char, len: usize) -> bool;
     fn robots_version() -> *const c_char;
 }
@@ -85,62 +328,508 @@ pub fn version() -> String {
 
 /// Checks if a user-agent string contains only valid characters [a-zA-Z_-].
 pub fn is_valid_user_agent(user_agent: &str) -> bool {
-    let c_ua = CString::new(user_agent).unwrap_or_default();
-    unsafe { robots_is_valid_user_agent(c_ua.as_ptr(), user_agent.len()) }
+    unsafe { robots_is_valid_user_agent(user_agent.as_ptr().cast(), user_agent.len()) }
+}
+
+/// Returns the optional C functions that the loaded library does not
+/// provide because it is older than this binding, in a fixed order.
+///
+/// Call this at startup to detect a version mismatch. The APIs backed by
+/// missing functions keep working with neutral results: no content signal,
+/// no timing, zero counts. With the `dlopen` feature and a library that
+/// cannot be loaded, every optional function is listed; see
+/// [`Capabilities::load_error`].
+pub fn missing_symbols() -> Vec<&'static str> {
+    symbols::missing()
 }
 
 /// Returns true if Content-Signal support is compiled in.
 pub fn content_signal_supported() -> bool {
-    unsafe { robots_content_signal_supported() }
+    symbols::robots_content_signal_supported().is_some_and(|f| unsafe { f() })
+}
+
+/// Optional parts of this build, from [`capabilities`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Version of the robots library in use, empty if it cannot be loaded.
+    pub library_version: String,
+    /// Why the library cannot be loaded (`dlopen` feature), or None.
+    pub load_error: Option<String>,
+    /// Content-Signal support is compiled into the library.
+    pub content_signal: bool,
+    /// Optional C functions the library lacks; see [`missing_symbols`].
+    pub missing_symbols: Vec<&'static str>,
+    /// The `html` feature: [`html`] report rendering.
+    pub html: bool,
+    /// The `serde` feature.
+    pub serde: bool,
+    /// The `rayon` feature: [`corpus`] evaluation.
+    pub rayon: bool,
+    /// The library is loaded at runtime (`dlopen` feature).
+    pub dlopen: bool,
+    /// The library is built from source with the crate (`vendored`).
+    pub vendored: bool,
+    /// The library is linked statically (`vendored`, `static` or
+    /// `ROBOTS_STATIC`).
+    pub static_link: bool,
+}
+
+/// Reports which optional parts are available in this build, for
+/// adapting behavior or showing operators the feature set.
+///
+/// Does not panic if the library cannot be loaded (`dlopen` feature);
+/// [`Capabilities::load_error`] says why.
+pub fn capabilities() -> Capabilities {
+    #[cfg(feature = "dlopen")]
+    let load_error = dynamic::ensure_loaded().err().map(|e| e.to_string());
+    #[cfg(not(feature = "dlopen"))]
+    let load_error = None;
+    Capabilities {
+        library_version: if load_error.is_none() {
+            version()
+        } else {
+            String::new()
+        },
+        load_error,
+        content_signal: content_signal_supported(),
+        missing_symbols: missing_symbols(),
+        html: cfg!(feature = "html"),
+        serde: cfg!(feature = "serde"),
+        rayon: cfg!(feature = "rayon"),
+        dlopen: cfg!(feature = "dlopen"),
+        vendored: cfg!(all(feature = "vendored", not(feature = "dlopen"))),
+        static_link: cfg!(robots_static),
+    }
+}
+
+/// Returns the values of the `Sitemap:` lines in `robots_txt`, in file order.
+///
+/// Returns an empty list if the library does not support this; see
+/// [`missing_symbols`].
+pub fn sitemaps(robots_txt: &str) -> Vec<String> {
+    RobotsTxt::parse(robots_txt).sitemaps()
+}
+
+/// Parses robots.txt and returns counters for ignored or unusual lines.
+///
+/// Returns all-zero statistics if the library does not support them; see
+/// [`try_parse_stats`].
+pub fn parse_stats(robots_txt: &str) -> ParseStats {
+    try_parse_stats(robots_txt).unwrap_or_default()
+}
+
+/// Same as [`parse_stats`], but fails with [`RobotsError::Unsupported`] if
+/// the loaded library is too old to compute them, and with
+/// [`RobotsError::InteriorNul`] if `robots_txt` contains a NUL byte.
+pub fn try_parse_stats(robots_txt: &str) -> Result<ParseStats, RobotsError> {
+    check_no_nul("robots_txt", robots_txt)?;
+    let parse = symbols::robots_parse_stats().ok_or(RobotsError::Unsupported {
+        symbol: "robots_parse_stats",
+    })?;
+    let mut stats = ParseStats::default();
+    unsafe {
+        parse(robots_txt.as_ptr().cast(), robots_txt.len(), &mut stats);
+    }
+    Ok(stats)
 }
 
 /// Robots.txt matcher - checks if URLs are allowed for given user-agents.
+///
+/// A matcher can be moved to another thread but not shared: each check
+/// overwrites state that the getters read. Use one matcher per thread, or
+/// [`SharedMatcher`] to share one.
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<robotstxt::RobotsMatcher>();
+/// ```
 pub struct RobotsMatcher {
-    ptr: *mut RobotsMatcherOpaque,
+    // None only once dropped, so a repeated free is a no-op.
+    ptr: Option<NonNull<RobotsMatcherOpaque>>,
+    profiling: Cell<bool>,
+    last_call: Cell<Duration>,
+    url_limit: Cell<Option<UrlLimit>>,
+    url_limit_hit: Cell<bool>,
+    strict: Cell<bool>,
+    // The last check rejected its URL without calling the C++ matcher,
+    // whose per-check state is from an earlier check.
+    rejected: Cell<bool>,
 }
 
 impl RobotsMatcher {
     /// Creates a new RobotsMatcher instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the C++ matcher cannot be allocated; see
+    /// [`try_new`](Self::try_new).
     pub fn new() -> Self {
-        let ptr = unsafe { robots_matcher_create() };
-        assert!(!ptr.is_null(), "Failed to create RobotsMatcher");
-        Self { ptr }
+        Self::try_new().expect("Failed to create RobotsMatcher")
+    }
+
+    /// Creates a new RobotsMatcher instance, failing with
+    /// [`RobotsError::NullMatcher`] if the C++ matcher cannot be allocated.
+    /// With the `dlopen` feature, fails with the load error if the library
+    /// cannot be loaded.
+    pub fn try_new() -> Result<Self, RobotsError> {
+        #[cfg(feature = "dlopen")]
+        dynamic::ensure_loaded()?;
+        let ptr = NonNull::new(unsafe { robots_matcher_create() });
+        if ptr.is_none() {
+            return Err(RobotsError::NullMatcher);
+        }
+        Ok(Self {
+            ptr,
+            profiling: Cell::new(false),
+            last_call: Cell::new(Duration::ZERO),
+            url_limit: Cell::new(None),
+            url_limit_hit: Cell::new(false),
+            strict: Cell::new(false),
+            rejected: Cell::new(false),
+        })
     }
 
     /// Checks if a URL is allowed for a single user-agent.
+    ///
+    /// URLs rejected by the [`UrlLimit`] are reported as disallowed; use
+    /// [`try_is_allowed`](Self::try_is_allowed) to tell them apart.
     pub fn is_allowed(&self, robots_txt: &str, user_agent: &str, url: &str) -> bool {
-        let c_robots = CString::new(robots_txt).unwrap_or_default();
-        let c_ua = CString::new(user_agent).unwrap_or_default();
-        let c_url = CString::new(url).unwrap_or_default();
+        match self.admit_url(url) {
+            Ok(url) => {
+                self.allowed_by_robots(robots_txt.as_bytes(), user_agent.as_bytes(), url.as_bytes())
+            }
+            Err(_) => false,
+        }
+    }
 
-        unsafe {
+    /// Same as [`is_allowed`](Self::is_allowed), also returning the line,
+    /// kind and pattern of the rule that decided.
+    ///
+    /// With a library too old to report the pattern, the kind is inferred
+    /// from the decision and the pattern is None.
+    pub fn check(&self, robots_txt: &str, user_agent: &str, url: &str) -> MatchResult {
+        let allowed = self.is_allowed(robots_txt, user_agent, url);
+        if self.rejected.get() {
+            return MatchResult {
+                allowed,
+                line: 0,
+                directive: MatchDirective::Default,
+                pattern: None,
+            };
+        }
+        let line = self.matching_line();
+        let mut result = MatchResult {
+            allowed,
+            line,
+            directive: match (line, allowed) {
+                (0, _) => MatchDirective::Default,
+                (_, true) => MatchDirective::Allow,
+                (_, false) => MatchDirective::Disallow,
+            },
+            pattern: None,
+        };
+        let Some(matching_rule) = symbols::robots_matching_rule() else {
+            return result;
+        };
+        let mut kind: c_int = 0;
+        let mut pattern: *const c_char = std::ptr::null();
+        let mut len = 0;
+        if unsafe { matching_rule(self.raw(), &mut kind, &mut pattern, &mut len) } {
+            result.directive = if kind == model::ALLOW {
+                MatchDirective::Allow
+            } else {
+                MatchDirective::Disallow
+            };
+            // SAFETY: the pattern is owned by the matcher and valid until the
+            // next check.
+            let bytes = unsafe { std::slice::from_raw_parts(pattern.cast::<u8>(), len) };
+            result.pattern = Some(String::from_utf8_lossy(bytes).into_owned());
+        }
+        result
+    }
+
+    /// Checks if a URL is allowed for several user-agents of one crawler,
+    /// such as `["Googlebot", "Googlebot-Image"]`.
+    ///
+    /// As with a single agent, the longest matching user-agent line is the
+    /// most specific: its group's rules apply, combined with any other group
+    /// naming an agent of the same length. With no agents, only the `*`
+    /// group applies.
+    pub fn is_allowed_any(&self, robots_txt: &str, user_agents: &[&str], url: &str) -> bool {
+        let Ok(url) = self.admit_url(url) else {
+            return false;
+        };
+        let ptrs: Vec<*const c_char> = user_agents.iter().map(|ua| ua.as_ptr().cast()).collect();
+        let lens: Vec<usize> = user_agents.iter().map(|ua| ua.len()).collect();
+        self.timed_check(|matcher| unsafe {
+            robots_allowed_by_robots_multi(
+                matcher,
+                robots_txt.as_ptr().cast(),
+                robots_txt.len(),
+                ptrs.as_ptr(),
+                lens.as_ptr(),
+                ptrs.len(),
+                url.as_ptr().cast(),
+                url.len(),
+            )
+        })
+    }
+
+    /// Checks each of `urls` for a single user-agent, parsing `robots_txt`
+    /// once. Returns one decision per URL, in order.
+    ///
+    /// The matcher state afterwards is that of the last URL.
+    pub fn are_allowed(&self, robots_txt: &str, user_agent: &str, urls: &[&str]) -> Vec<bool> {
+        let robots = RobotsTxt::parse(robots_txt);
+        urls.iter()
+            .map(|url| self.is_allowed_parsed(&robots, user_agent, url))
+            .collect()
+    }
+
+    /// Same as [`is_allowed`](Self::is_allowed), for input that is not
+    /// known to be UTF-8, such as a robots.txt body straight from an HTTP
+    /// response. The bytes are passed to the C++ matcher as they are.
+    pub fn is_allowed_bytes(&self, robots_txt: &[u8], user_agent: &[u8], url: &[u8]) -> bool {
+        match self.admit_url_bytes(url) {
+            Ok(url) => self.allowed_by_robots(robots_txt, user_agent, url),
+            Err(_) => false,
+        }
+    }
+
+    /// Checks if a URL is allowed for a single user-agent, returning an error
+    /// instead of a decision for input the matcher refuses to check.
+    ///
+    /// Unlike [`is_allowed`](Self::is_allowed), this rejects URLs with a
+    /// scheme other than `http` or `https`, and input containing NUL bytes,
+    /// which the C++ parser treats as the end of a line. URLs without a
+    /// scheme, such as `/path` or `example.com/path`, are accepted.
+    pub fn try_is_allowed(
+        &self,
+        robots_txt: &str,
+        user_agent: &str,
+        url: &str,
+    ) -> Result<bool, RobotsError> {
+        check_no_nul("robots_txt", robots_txt)?;
+        let url = self.check_input(user_agent, url)?;
+        Ok(self.allowed_by_robots(robots_txt.as_bytes(), user_agent.as_bytes(), url.as_bytes()))
+    }
+
+    /// Same as [`is_allowed_parsed`](Self::is_allowed_parsed), with the input
+    /// checks of [`try_is_allowed`](Self::try_is_allowed).
+    pub fn try_is_allowed_parsed(
+        &self,
+        robots: &RobotsTxt,
+        user_agent: &str,
+        url: &str,
+    ) -> Result<bool, RobotsError> {
+        let url = self.check_input(user_agent, url)?;
+        Ok(self.check_parsed(robots, user_agent, url))
+    }
+
+    fn check_input<'a>(&self, user_agent: &str, url: &'a str) -> Result<&'a str, RobotsError> {
+        check_no_nul("user_agent", user_agent)?;
+        check_no_nul("url", url)?;
+        check_scheme(url)?;
+        self.admit_url(url)
+    }
+
+    /// Sets the maximum URL length and what happens to longer URLs. There is
+    /// no limit by default.
+    pub fn set_url_limit(&self, limit: Option<UrlLimit>) {
+        self.url_limit.set(limit);
+    }
+
+    /// Returns true if the URL of the last check exceeded the [`UrlLimit`]
+    /// and was truncated or rejected.
+    pub fn url_limit_hit(&self) -> bool {
+        self.url_limit_hit.get()
+    }
+
+    /// Enables or disables strict mode, off by default.
+    ///
+    /// In strict mode, directives the parser accepts only leniently are
+    /// ignored: typo variants of a key, such as `Dissalow`, and lines
+    /// without a colon, such as `Disallow /x`. URLs with a scheme other than
+    /// `http` or `https` are rejected as by
+    /// [`try_is_allowed`](Self::try_is_allowed), and reported as disallowed
+    /// by the other checks. Checks against a [`RobotsTxt`] are as strict as
+    /// its parse; see [`RobotsTxt::try_parse_strict`].
+    ///
+    /// Fails with [`RobotsError::Unsupported`] if the library is too old.
+    pub fn set_strict(&self, enabled: bool) -> Result<(), RobotsError> {
+        let set_strict = symbols::robots_set_strict().ok_or(RobotsError::Unsupported {
+            symbol: "robots_set_strict",
+        })?;
+        unsafe { set_strict(self.raw(), enabled) };
+        self.strict.set(enabled);
+        Ok(())
+    }
+
+    /// Returns true if strict mode is enabled.
+    pub fn is_strict(&self) -> bool {
+        self.strict.get()
+    }
+
+    fn admit_url<'a>(&self, url: &'a str) -> Result<&'a str, RobotsError> {
+        // Truncation never splits a UTF-8 sequence, so this stays a str.
+        self.admit_url_bytes(url.as_bytes())
+            .map(|kept| &url[..kept.len()])
+    }
+
+    /// Applies the scheme check of strict mode and the URL limit.
+    fn admit_url_bytes<'a>(&self, url: &'a [u8]) -> Result<&'a [u8], RobotsError> {
+        let admitted = self.limit_url(url);
+        self.rejected.set(admitted.is_err());
+        admitted
+    }
+
+    fn limit_url<'a>(&self, url: &'a [u8]) -> Result<&'a [u8], RobotsError> {
+        if self.strict.get() {
+            self.url_limit_hit.set(false);
+            check_scheme(&String::from_utf8_lossy(url))?;
+        }
+        let limit = match self.url_limit.get() {
+            Some(limit) if url.len() > limit.max_len => limit,
+            _ => {
+                self.url_limit_hit.set(false);
+                return Ok(url);
+            }
+        };
+        self.url_limit_hit.set(true);
+        match limit.policy {
+            UrlLengthPolicy::Truncate => {
+                // Back off to the start of a UTF-8 sequence.
+                let mut end = limit.max_len;
+                while end > 0 && url[end] & 0xC0 == 0x80 {
+                    end -= 1;
+                }
+                Ok(&url[..end])
+            }
+            UrlLengthPolicy::Reject => Err(RobotsError::UrlTooLong {
+                len: url.len(),
+                max: limit.max_len,
+            }),
+        }
+    }
+
+    /// Checks if a URL is allowed for a single user-agent by a robots.txt
+    /// parsed ahead of time with [`RobotsTxt::parse`].
+    ///
+    /// Gives the same decision and matcher state as
+    /// [`is_allowed`](Self::is_allowed) on the original text.
+    pub fn is_allowed_parsed(&self, robots: &RobotsTxt, user_agent: &str, url: &str) -> bool {
+        match self.admit_url(url) {
+            Ok(url) => self.check_parsed(robots, user_agent, url),
+            Err(_) => false,
+        }
+    }
+
+    fn allowed_by_robots(&self, robots_txt: &[u8], user_agent: &[u8], url: &[u8]) -> bool {
+        self.timed_check(|matcher| unsafe {
             robots_allowed_by_robots(
-                self.ptr,
-                c_robots.as_ptr(),
+                matcher,
+                robots_txt.as_ptr().cast(),
                 robots_txt.len(),
-                c_ua.as_ptr(),
+                user_agent.as_ptr().cast(),
+                user_agent.len(),
+                url.as_ptr().cast(),
+                url.len(),
+            )
+        })
+    }
+
+    fn check_parsed(&self, robots: &RobotsTxt, user_agent: &str, url: &str) -> bool {
+        let (Some(ptr), Some(allowed_by_parsed)) =
+            (robots.ptr, symbols::robots_allowed_by_parsed())
+        else {
+            let text = robots.text.as_deref().unwrap_or_default();
+            return self.allowed_by_robots(text.as_bytes(), user_agent.as_bytes(), url.as_bytes());
+        };
+        self.timed_check(|matcher| unsafe {
+            allowed_by_parsed(
+                matcher,
+                ptr.as_ptr(),
+                user_agent.as_ptr().cast(),
                 user_agent.len(),
-                c_url.as_ptr(),
+                url.as_ptr().cast(),
                 url.len(),
             )
+        })
+    }
+
+    /// Runs `call` on the matcher, recording the call duration when
+    /// profiling. Strings are passed as pointer and length; the C API does
+    /// not need them NUL-terminated.
+    fn timed_check(&self, call: impl FnOnce(*mut RobotsMatcherOpaque) -> bool) -> bool {
+        let start = self.profiling.get().then(Instant::now);
+        let allowed = call(self.raw());
+        if let Some(start) = start {
+            self.last_call.set(start.elapsed());
         }
+        allowed
     }
 
-    /// Returns the line number that matched, or 0 if no match.
+    /// Enables or disables timing of each check. Profiling is off by default
+    /// because it adds a few clock reads per robots.txt line.
+    pub fn set_profiling(&self, enabled: bool) {
+        self.profiling.set(enabled);
+        if let Some(set_profiling) = symbols::robots_set_profiling() {
+            unsafe { set_profiling(self.raw(), enabled) }
+        }
+    }
+
+    /// Returns the timing breakdown of the last check, or None if profiling
+    /// is disabled or unsupported by the library.
+    pub fn last_timing(&self) -> Option<DecisionTiming> {
+        let get_timing = symbols::robots_get_timing()?;
+        let mut raw = RawTiming::default();
+        if !unsafe { get_timing(self.raw(), &mut raw) } {
+            return None;
+        }
+        let matching = raw.group_selection_ns + raw.rule_matching_ns;
+        let total = Duration::from_nanos(raw.total_ns);
+        Some(DecisionTiming {
+            url_parse: Duration::from_nanos(raw.url_parse_ns),
+            parsing: Duration::from_nanos(raw.total_ns.saturating_sub(raw.url_parse_ns + matching)),
+            group_selection: Duration::from_nanos(raw.group_selection_ns),
+            rule_matching: Duration::from_nanos(raw.rule_matching_ns),
+            ffi_overhead: self.last_call.get().saturating_sub(total),
+        })
+    }
+
+    /// Returns the line number that matched, or 0 if no rule matched or
+    /// the URL was rejected without a check.
     pub fn matching_line(&self) -> i32 {
-        unsafe { robots_matching_line(self.ptr) }
+        if self.rejected.get() {
+            return 0;
+        }
+        unsafe { robots_matching_line(self.raw()) }
     }
 
     /// Returns true if a specific user-agent block was found (not just '*').
     pub fn ever_seen_specific_agent(&self) -> bool {
-        unsafe { robots_ever_seen_specific_agent(self.ptr) }
+        unsafe { robots_ever_seen_specific_agent(self.raw()) }
+    }
+
+    /// Returns the number of Allow/Disallow rules in the group(s) that applied
+    /// to the last checked user-agent.
+    pub fn effective_rule_count(&self) -> usize {
+        symbols::robots_effective_rule_count().map_or(0, |f| unsafe { f(self.raw()) })
+    }
+
+    /// Returns the combined pattern length in bytes of the rules counted by
+    /// [`effective_rule_count`](Self::effective_rule_count).
+    pub fn effective_rule_bytes(&self) -> usize {
+        symbols::robots_effective_rule_bytes().map_or(0, |f| unsafe { f(self.raw()) })
     }
 
     /// Returns the crawl-delay in seconds, or None if not specified.
     pub fn crawl_delay(&self) -> Option<f64> {
         unsafe {
-            if robots_has_crawl_delay(self.ptr) {
-                Some(robots_get_crawl_delay(self.ptr))
+            if robots_has_crawl_delay(self.raw()) {
+                Some(robots_get_crawl_delay(self.raw()))
             } else {
                 None
             }
@@ -150,11 +839,14 @@ impl RobotsMatcher {
     /// Returns the request-rate, or None if not specified.
     pub fn request_rate(&self) -> Option<RequestRate> {
         unsafe {
+            if !robots_has_request_rate(self.raw()) {
+                return None;
+            }
             let mut rate = RequestRate {
                 requests: 0,
                 seconds: 0,
             };
-            if robots_get_request_rate(self.ptr, &mut rate) {
+            if robots_get_request_rate(self.raw(), &mut rate) {
                 Some(rate)
             } else {
                 None
@@ -162,10 +854,59 @@ impl RobotsMatcher {
         }
     }
 
+    /// Returns the crawl-delay that `robots_txt` sets for `user_agent`,
+    /// without checking a URL first. Replaces the state of the last check.
+    ///
+    /// As with [`crawl_delay`](Self::crawl_delay), a value missing from the
+    /// agent's group is taken from the `*` group.
+    pub fn crawl_delay_for(&self, robots_txt: &str, user_agent: &str) -> Option<f64> {
+        self.select_group(robots_txt, user_agent);
+        self.crawl_delay()
+    }
+
+    /// Returns the request-rate that `robots_txt` sets for `user_agent`,
+    /// without checking a URL first. Replaces the state of the last check.
+    ///
+    /// As with [`request_rate`](Self::request_rate), a value missing from
+    /// the agent's group is taken from the `*` group.
+    pub fn request_rate_for(&self, robots_txt: &str, user_agent: &str) -> Option<RequestRate> {
+        self.select_group(robots_txt, user_agent);
+        self.request_rate()
+    }
+
+    // Crawl-delay and request-rate are recorded for the group that applies
+    // to the agent, whatever the URL.
+    fn select_group(&self, robots_txt: &str, user_agent: &str) {
+        self.allowed_by_robots(robots_txt.as_bytes(), user_agent.as_bytes(), b"/");
+    }
+
+    /// Same as [`content_signal`](Self::content_signal), but fails with
+    /// [`RobotsError::Unsupported`] if the library has no content-signal
+    /// functions, instead of returning None.
+    pub fn try_content_signal(&self) -> Result<Option<ContentSignal>, RobotsError> {
+        for (symbol, present) in [
+            (
+                "robots_has_content_signal",
+                symbols::robots_has_content_signal().is_some(),
+            ),
+            (
+                "robots_get_content_signal",
+                symbols::robots_get_content_signal().is_some(),
+            ),
+        ] {
+            if !present {
+                return Err(RobotsError::Unsupported { symbol });
+            }
+        }
+        Ok(self.content_signal())
+    }
+
     /// Returns the content-signal values, or None if not specified.
     pub fn content_signal(&self) -> Option<ContentSignal> {
+        let has_signal = symbols::robots_has_content_signal()?;
+        let get_signal = symbols::robots_get_content_signal()?;
         unsafe {
-            if !robots_content_signal_supported() {
+            if !content_signal_supported() || !has_signal(self.raw()) {
                 return None;
             }
             let mut signal = ContentSignal {
@@ -173,7 +914,7 @@ impl RobotsMatcher {
                 ai_input: -1,
                 search: -1,
             };
-            if robots_get_content_signal(self.ptr, &mut signal) {
+            if get_signal(self.raw(), &mut signal) {
                 Some(signal)
             } else {
                 None
@@ -183,40 +924,191 @@ impl RobotsMatcher {
 
     /// Returns true if AI training is allowed (defaults to true if not specified).
     pub fn allows_ai_train(&self) -> bool {
-        unsafe { robots_allows_ai_train(self.ptr) }
+        symbols::robots_allows_ai_train().is_none_or(|f| unsafe { f(self.raw()) })
     }
 
     /// Returns true if AI input is allowed (defaults to true if not specified).
     pub fn allows_ai_input(&self) -> bool {
-        unsafe { robots_allows_ai_input(self.ptr) }
+        symbols::robots_allows_ai_input().is_none_or(|f| unsafe { f(self.raw()) })
     }
 
     /// Returns true if search indexing is allowed (defaults to true if not specified).
     pub fn allows_search(&self) -> bool {
-        unsafe { robots_allows_search(self.ptr) }
+        symbols::robots_allows_search().is_none_or(|f| unsafe { f(self.raw()) })
+    }
+}
+
+fn check_no_nul(field: &'static str, value: &str) -> Result<(), RobotsError> {
+    if value.contains('\0') {
+        Err(RobotsError::InteriorNul { field })
+    } else {
+        Ok(())
+    }
+}
+
+/// Fails if `url` starts with a scheme other than http or https.
+fn check_scheme(url: &str) -> Result<(), RobotsError> {
+    let Some((scheme, rest)) = url.split_once(':') else {
+        return Ok(());
+    };
+    let is_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    // "example.com:8080/path" is a host and port, not a scheme.
+    if !is_scheme || rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return Ok(());
+    }
+    if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") {
+        Ok(())
+    } else {
+        Err(RobotsError::UnsupportedScheme {
+            scheme: scheme.to_ascii_lowercase(),
+        })
+    }
+}
+
+/// A robots.txt parsed once, for checking many URLs without re-parsing.
+///
+/// Parsing dominates the cost of a check, so crawlers that check many URLs
+/// against the same robots.txt should parse it once and keep the
+/// `RobotsTxt`. It is immutable and can be shared between threads.
+///
+/// ```no_run
+/// use robotstxt::RobotsTxt;
+///
+/// let robots = RobotsTxt::parse("User-agent: *\nDisallow: /admin/\n");
+/// assert!(robots.is_allowed("Googlebot", "https://example.com/page"));
+/// assert!(!robots.is_allowed("Googlebot", "https://example.com/admin/x"));
+/// ```
+pub struct RobotsTxt {
+    ptr: Option<NonNull<RobotsTxtOpaque>>,
+    // Kept only if the library cannot pre-parse; checks then parse this.
+    text: Option<Box<str>>,
+}
+
+impl RobotsTxt {
+    /// Parses `robots_txt`.
+    pub fn parse(robots_txt: &str) -> RobotsTxt {
+        let ptr = symbols::robots_txt_parse().and_then(|parse| {
+            NonNull::new(unsafe { parse(robots_txt.as_ptr().cast(), robots_txt.len()) })
+        });
+        RobotsTxt {
+            ptr,
+            text: ptr.is_none().then(|| robots_txt.into()),
+        }
+    }
+
+    /// Same as [`parse`](Self::parse), but fails with
+    /// [`RobotsError::InteriorNul`] if `robots_txt` contains a NUL byte.
+    pub fn try_parse(robots_txt: &str) -> Result<RobotsTxt, RobotsError> {
+        check_no_nul("robots_txt", robots_txt)?;
+        Ok(RobotsTxt::parse(robots_txt))
+    }
+
+    /// Same as [`try_parse`](Self::try_parse), but ignores typo variants of
+    /// keys and lines without a colon, as in
+    /// [`RobotsMatcher::set_strict`]. Fails with [`RobotsError::Unsupported`]
+    /// if the library is too old, and with [`RobotsError::NullMatcher`] if
+    /// the parsed robots.txt cannot be allocated.
+    pub fn try_parse_strict(robots_txt: &str) -> Result<RobotsTxt, RobotsError> {
+        check_no_nul("robots_txt", robots_txt)?;
+        let parse = symbols::robots_txt_parse_strict().ok_or(RobotsError::Unsupported {
+            symbol: "robots_txt_parse_strict",
+        })?;
+        let ptr = NonNull::new(unsafe { parse(robots_txt.as_ptr().cast(), robots_txt.len()) })
+            .ok_or(RobotsError::NullMatcher)?;
+        Ok(RobotsTxt {
+            ptr: Some(ptr),
+            text: None,
+        })
+    }
+
+    /// Returns the values of the `Sitemap:` lines, in file order. Sitemaps
+    /// are not tied to a group, so this includes lines anywhere in the file.
+    ///
+    /// Returns an empty list if the library does not support this; see
+    /// [`missing_symbols`].
+    pub fn sitemaps(&self) -> Vec<String> {
+        let (Some(ptr), Some(count), Some(get)) = (
+            self.ptr,
+            symbols::robots_txt_sitemap_count(),
+            symbols::robots_txt_sitemap(),
+        ) else {
+            return Vec::new();
+        };
+        let ptr = ptr.as_ptr().cast_const();
+        (0..unsafe { count(ptr) })
+            .filter_map(|i| {
+                let mut len = 0;
+                let value = unsafe { get(ptr, i, &mut len) };
+                if value.is_null() {
+                    return None;
+                }
+                // The parser splits lines on bytes, so a value may end in
+                // the middle of a character.
+                let bytes = unsafe { std::slice::from_raw_parts(value.cast::<u8>(), len) };
+                Some(String::from_utf8_lossy(bytes).into_owned())
+            })
+            .collect()
+    }
+
+    /// Checks if a URL is allowed for a single user-agent.
+    ///
+    /// Uses a matcher per thread; to read the matching line or crawl-delay
+    /// of the check, use [`RobotsMatcher::is_allowed_parsed`] instead.
+    pub fn is_allowed(&self, user_agent: &str, url: &str) -> bool {
+        thread_local! {
+            static MATCHER: RobotsMatcher = RobotsMatcher::new();
+        }
+        MATCHER.with(|matcher| matcher.is_allowed_parsed(self, user_agent, url))
     }
 }
 
+impl Drop for RobotsTxt {
+    fn drop(&mut self) {
+        if let (Some(ptr), Some(free)) = (self.ptr.take(), symbols::robots_txt_free()) {
+            unsafe { free(ptr.as_ptr()) }
+        }
+    }
+}
+
+// The parsed document is never modified after robots_txt_parse() returns.
+unsafe impl Send for RobotsTxt {}
+unsafe impl Sync for RobotsTxt {}
+
 impl Default for RobotsMatcher {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Drop for RobotsMatcher {
-    fn drop(&mut self) {
-        if !self.ptr.is_null() {
+impl RobotsMatcher {
+    fn raw(&self) -> *mut RobotsMatcherOpaque {
+        self.ptr.map_or(std::ptr::null_mut(), NonNull::as_ptr)
+    }
+}
+
+impl RobotsMatcher {
+    /// Frees the C++ matcher; later calls and checks see a null matcher.
+    fn release(&mut self) {
+        if let Some(ptr) = self.ptr.take() {
             unsafe {
-                robots_matcher_free(self.ptr);
+                robots_matcher_free(ptr.as_ptr());
             }
         }
     }
 }
 
-// RobotsMatcher is Send + Sync safe because the underlying C++ implementation
-// is thread-safe for read operations after parsing.
+impl Drop for RobotsMatcher {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+// The C++ matcher has no thread affinity, so it may move between threads.
+// It is not Sync: every check writes the matching line and group state.
 unsafe impl Send for RobotsMatcher {}
-unsafe impl Sync for RobotsMatcher {}
 
 #[cfg(test)]
 mod tests {
@@ -228,6 +1120,80 @@ mod tests {
         assert!(!v.is_empty());
     }
 
+    #[test]
+    fn test_are_allowed() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: FooBot\nDisallow: /x\nAllow: /x/y\n";
+        let urls = [
+            "http://foo.bar/",
+            "http://foo.bar/x/z",
+            "http://foo.bar/x/y",
+        ];
+        assert_eq!(m.are_allowed(robots, "FooBot", &urls), [true, false, true]);
+        assert_eq!(m.matching_line(), 3);
+        assert!(m.are_allowed(robots, "FooBot", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: *\nDisallow: /x\n\nUser-agent: FooBot\nDisallow: /a*.gif$\nAllow: /a/index.html\n";
+        let result = m.check(robots, "FooBot", "http://foo.bar/a/b.gif");
+        assert_eq!(
+            result,
+            MatchResult {
+                allowed: false,
+                line: 5,
+                directive: MatchDirective::Disallow,
+                pattern: Some("/a*.gif$".to_string()),
+            }
+        );
+        let result = m.check(robots, "FooBot", "http://foo.bar/a/");
+        assert!(result.allowed);
+        assert_eq!(result.directive, MatchDirective::Allow);
+        assert_eq!(result.pattern.as_deref(), Some("/a/index.html"));
+        let result = m.check(robots, "FooBot", "http://foo.bar/x");
+        assert_eq!(
+            (result.line, result.directive),
+            (0, MatchDirective::Default)
+        );
+        assert_eq!(result.pattern, None);
+        assert_eq!(m.check(robots, "BarBot", "/x").line, 2);
+
+        // A rejected URL reports no rule, not the previous check's.
+        m.set_url_limit(Some(UrlLimit {
+            max_len: 20,
+            policy: UrlLengthPolicy::Reject,
+        }));
+        let result = m.check(robots, "BarBot", "http://foo.bar/x/long/path");
+        assert_eq!(
+            result,
+            MatchResult {
+                allowed: false,
+                line: 0,
+                directive: MatchDirective::Default,
+                pattern: None,
+            }
+        );
+        assert_eq!(m.matching_line(), 0);
+        assert_eq!(m.check(robots, "BarBot", "/x").line, 2);
+        m.set_url_limit(None);
+        if m.set_strict(true).is_ok() {
+            assert_eq!(m.check(robots, "BarBot", "/x").line, 2);
+            assert_eq!(m.check(robots, "BarBot", "ftp://foo.bar/x").line, 0);
+            assert_eq!(m.matching_line(), 0);
+        }
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let caps = capabilities();
+        assert_eq!(caps.library_version, version());
+        assert_eq!(caps.content_signal, content_signal_supported());
+        assert_eq!(caps.html, cfg!(feature = "html"));
+        assert_eq!(caps.dlopen, cfg!(feature = "dlopen"));
+    }
+
     #[test]
     fn test_is_valid_user_agent() {
         assert!(is_valid_user_agent("Googlebot"));
@@ -235,6 +1201,19 @@ mod tests {
         assert!(!is_valid_user_agent("Bot/1.0"));
     }
 
+    #[test]
+    fn test_parse_stats() {
+        let robots = "Disallow: /early\n# comment\n\nUser-agent: *\nDissalow: /a\nHost: example.com\ngarbage line here\n";
+        let stats = parse_stats(robots);
+        assert_eq!(stats.total_lines, 8);
+        assert_eq!(stats.directives, 4);
+        assert_eq!(stats.unknown_directives, 1);
+        assert_eq!(stats.malformed_lines, 1);
+        assert_eq!(stats.rules_outside_groups, 1);
+        assert_eq!(stats.typo_directives, 1);
+        assert_eq!(stats.ignored_lines(), 3);
+    }
+
     #[test]
     fn test_basic_allow() {
         let m = RobotsMatcher::new();
@@ -250,11 +1229,403 @@ mod tests {
         assert!(m.is_allowed(robots, "Googlebot", "https://example.com/public"));
     }
 
+    #[test]
+    fn test_effective_rules() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: *\nDisallow: /a\n\nUser-agent: Googlebot\nAllow: /index.html\nDisallow: /private/\n";
+        m.is_allowed(robots, "Googlebot", "https://example.com/");
+        assert_eq!(m.effective_rule_count(), 2);
+        assert_eq!(
+            m.effective_rule_bytes(),
+            "/index.html".len() + "/private/".len()
+        );
+
+        m.is_allowed(robots, "Bingbot", "https://example.com/");
+        assert_eq!(m.effective_rule_count(), 1);
+        assert_eq!(m.effective_rule_bytes(), 2);
+    }
+
+    #[test]
+    fn test_profiling() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: *\nDisallow: /admin/\n";
+        m.is_allowed(robots, "Googlebot", "https://example.com/");
+        assert_eq!(m.last_timing(), None);
+
+        m.set_profiling(true);
+        assert!(!m.is_allowed(robots, "Googlebot", "https://example.com/admin/x"));
+        let timing = m.last_timing().unwrap();
+        assert!(timing.total() > Duration::ZERO);
+        assert!(timing.total() >= timing.rule_matching);
+    }
+
+    #[test]
+    fn test_user_agent_change_between_checks() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: Googlebot\nDisallow: /\n";
+        assert!(!m.is_allowed(robots, "Googlebot", "https://example.com/"));
+        assert!(!m.is_allowed(robots, "Googlebot", "https://example.com/"));
+        assert!(m.is_allowed(robots, "Bingbot", "https://example.com/"));
+        assert!(!m.is_allowed(robots, "Googlebot", "https://example.com/"));
+    }
+
+    #[test]
+    fn test_url_limit() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: *\nDisallow: /private\n";
+        let url = "https://example.com/private/secret";
+
+        m.set_url_limit(Some(UrlLimit {
+            max_len: 20,
+            policy: UrlLengthPolicy::Truncate,
+        }));
+        assert_eq!(m.try_is_allowed(robots, "Googlebot", url), Ok(true));
+        assert!(m.url_limit_hit());
+
+        m.set_url_limit(Some(UrlLimit {
+            max_len: 20,
+            policy: UrlLengthPolicy::Reject,
+        }));
+        let err = m.try_is_allowed(robots, "Googlebot", url).unwrap_err();
+        assert_eq!(err, RobotsError::UrlTooLong { len: 34, max: 20 });
+        assert_eq!(err.category(), ErrorCategory::PolicyDenied);
+        assert!(!m.is_allowed(robots, "Googlebot", url));
+
+        assert!(m.is_allowed(robots, "Googlebot", "https://example.com/"));
+        assert!(!m.url_limit_hit());
+    }
+
+    #[test]
+    fn test_unsupported_scheme() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: *\nDisallow: /admin/\n";
+        for url in [
+            "ftp://example.com/a",
+            "javascript:alert(1)",
+            "MAILTO:me@example.com",
+        ] {
+            let err = m.try_is_allowed(robots, "Googlebot", url).unwrap_err();
+            assert!(matches!(err, RobotsError::UnsupportedScheme { .. }));
+            assert_eq!(err.category(), ErrorCategory::Permanent);
+        }
+        for url in ["HTTPS://example.com/a", "/a", "example.com:8080/a"] {
+            assert_eq!(m.try_is_allowed(robots, "Googlebot", url), Ok(true));
+        }
+        assert_eq!(
+            m.try_is_allowed(robots, "Googlebot", "http://example.com/admin/"),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_interior_nul() {
+        let m = RobotsMatcher::try_new().unwrap();
+        let robots = "User-agent: *\nDisallow: /private/\n";
+        assert_eq!(
+            m.try_is_allowed("User-agent: *\0\nDisallow: /\n", "Bot", "/"),
+            Err(RobotsError::InteriorNul {
+                field: "robots_txt"
+            })
+        );
+        assert_eq!(
+            m.try_is_allowed(robots, "Bot\0", "/"),
+            Err(RobotsError::InteriorNul {
+                field: "user_agent"
+            })
+        );
+        assert_eq!(
+            m.try_is_allowed(robots, "Bot", "/private/\0"),
+            Err(RobotsError::InteriorNul { field: "url" })
+        );
+        assert!(RobotsTxt::try_parse("\0").is_err());
+        assert!(try_parse_stats("\0").is_err());
+        assert!(!is_valid_user_agent("Bot\0"));
+
+        // A NUL after the robots.txt no longer discards the whole file.
+        assert!(!m.is_allowed(
+            "User-agent: *\nDisallow: /private/\n\0",
+            "Bot",
+            "/private/x"
+        ));
+        assert_eq!(m.try_content_signal().is_ok(), missing_symbols().is_empty());
+    }
+
+    #[test]
+    fn test_multiple_agents() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: Googlebot\nDisallow: /a/\nAllow: /a/b/\n\n\
+                      User-agent: Googlebot-Image\nDisallow: /a/b/c/\n\n\
+                      User-agent: *\nDisallow: /\n";
+        let agents = ["Googlebot", "Googlebot-Image"];
+        // The Googlebot-Image group is more specific and replaces Googlebot's.
+        assert!(m.is_allowed_any(robots, &agents, "/a/x"));
+        assert!(!m.is_allowed_any(robots, &agents, "/a/b/c/x"));
+        assert_eq!(m.matching_line(), 6);
+        assert!(m.is_allowed_any(robots, &["Googlebot"], "/a/b/c/x"));
+        assert!(!m.is_allowed_any(robots, &["Googlebot"], "/a/x"));
+        assert!(!m.is_allowed_any(robots, &[], "/a/b/x"));
+    }
+
+    #[test]
+    fn test_ip_literal_urls() {
+        let m = RobotsMatcher::new();
+        let robots = "User-agent: *\nDisallow: /private/\n";
+        for url in [
+            "http://192.0.2.1:8080/private/x",
+            "http://[2001:db8::1]/private/x",
+            "https://user@[::1]:8443/private/x?q#f",
+            "192.0.2.1:8080/private/x",
+        ] {
+            assert_eq!(m.try_is_allowed(robots, "Bot", url), Ok(false), "{url}");
+        }
+        assert!(m.is_allowed(robots, "Bot", "http://[::1]:8080/public"));
+    }
+
+    #[test]
+    fn test_sitemaps() {
+        let robots = "Sitemap: https://example.com/a.xml\n\
+                      User-agent: *\nDisallow: /\n\
+                      sitemap:https://example.com/b.xml # comment\n\
+                      Site-map: https://example.com/c.xml\n";
+        let expected = if missing_symbols().contains(&"robots_txt_sitemap") {
+            vec![]
+        } else {
+            vec![
+                "https://example.com/a.xml",
+                "https://example.com/b.xml",
+                "https://example.com/c.xml",
+            ]
+        };
+        assert_eq!(sitemaps(robots), expected);
+        assert_eq!(RobotsTxt::parse(robots).sitemaps(), expected);
+        assert!(sitemaps("User-agent: *\n").is_empty());
+    }
+
+    #[test]
+    fn test_bytes_input() {
+        let m = RobotsMatcher::new();
+        // Latin-1 body: the 0xE9 byte is not valid UTF-8.
+        let robots = b"User-agent: *\nDisallow: /caf\xe9/\nDisallow: /private/\n";
+        assert!(!m.is_allowed_bytes(robots, b"Bot", b"/private/x"));
+        assert_eq!(m.matching_line(), 3);
+        assert!(m.is_allowed_bytes(robots, b"Bot", b"/public/x"));
+
+        m.set_url_limit(Some(UrlLimit {
+            max_len: 9,
+            policy: UrlLengthPolicy::Truncate,
+        }));
+        // Truncates to "/private" rather than cutting into the e-acute.
+        assert!(m.is_allowed_bytes(robots, b"Bot", "/private\u{e9}/x".as_bytes()));
+        assert!(m.url_limit_hit());
+    }
+
+    #[test]
+    fn test_content_signal_policy() {
+        let raw = ContentSignal {
+            ai_train: 0,
+            ai_input: -1,
+            search: 1,
+        };
+        let policy = ContentSignalPolicy::from(raw);
+        assert_eq!(
+            policy,
+            ContentSignalPolicy {
+                ai_train: Signal::No,
+                ai_input: Signal::Unset,
+                search: Signal::Yes,
+            }
+        );
+        assert!(!policy.ai_train.allows() && policy.ai_input.allows());
+        let back = ContentSignal::from(policy);
+        assert_eq!((back.ai_train, back.ai_input, back.search), (0, -1, 1));
+        assert_eq!(Signal::from(7), Signal::Unset);
+    }
+
     #[test]
     fn test_crawl_delay() {
         let m = RobotsMatcher::new();
         let robots = "User-agent: *\nCrawl-delay: 2.5\nDisallow:\n";
         m.is_allowed(robots, "Googlebot", "https://example.com/");
         assert_eq!(m.crawl_delay(), Some(2.5));
+
+        let robots = "User-agent: FooBot\nCrawl-delay: 3\nDisallow: /\n\n\
+                      User-agent: *\nRequest-rate: 1/5\n";
+        let m = RobotsMatcher::new();
+        assert_eq!(m.crawl_delay_for(robots, "FooBot"), Some(3.0));
+        assert_eq!(m.crawl_delay_for(robots, "BarBot"), None);
+        // A value missing from the agent's group comes from the `*` group.
+        for agent in ["FooBot", "BarBot"] {
+            let rate = m.request_rate_for(robots, agent).unwrap();
+            assert_eq!((rate.requests, rate.seconds), (1, 5));
+        }
+    }
+
+    #[test]
+    fn test_parsed_matches_text() {
+        let robots_txt = "User-agent: FooBot\nDisallow: /foo/\nAllow: /foo/bar/index.html\nCrawl-delay: 3\n\n\
+                          User-agent: *\nDisallow: /private/\nAllow: /private/$\nRequest-rate: 1/5\nSitemap: https://e.com/s.xml\n";
+        let robots = RobotsTxt::parse(robots_txt);
+        let by_text = RobotsMatcher::new();
+        let by_parsed = RobotsMatcher::new();
+        for agent in ["FooBot", "BarBot", "FooBot-Image"] {
+            for path in [
+                "/",
+                "/foo/x",
+                "/foo/bar/",
+                "/private/",
+                "/private/x",
+                "/other",
+            ] {
+                let url = format!("https://e.com{path}");
+                assert_eq!(
+                    by_parsed.is_allowed_parsed(&robots, agent, &url),
+                    by_text.is_allowed(robots_txt, agent, &url),
+                    "{agent} {url}"
+                );
+                assert_eq!(by_parsed.matching_line(), by_text.matching_line());
+                assert_eq!(by_parsed.crawl_delay(), by_text.crawl_delay());
+                let rate = |m: &RobotsMatcher| m.request_rate().map(|r| (r.requests, r.seconds));
+                assert_eq!(rate(&by_parsed), rate(&by_text));
+                assert_eq!(
+                    by_parsed.effective_rule_count(),
+                    by_text.effective_rule_count()
+                );
+                assert_eq!(
+                    robots.is_allowed(agent, &url),
+                    by_text.is_allowed(robots_txt, agent, &url)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_i18n_corpus() {
+        macro_rules! corpus {
+            ($file:literal) => {
+                include_str!(concat!("../testdata/i18n/", $file))
+            };
+        }
+        let m = RobotsMatcher::new();
+        let cases: &[(&str, &str, &str, bool)] = &[
+            (
+                corpus!("emoji_path.txt"),
+                "Bot",
+                "https://e.com/🚀/x",
+                false,
+            ),
+            (
+                corpus!("emoji_path.txt"),
+                "Bot",
+                "https://e.com/%F0%9F%9A%80/x",
+                false,
+            ),
+            (
+                corpus!("rtl_path.txt"),
+                "Bot",
+                "https://e.com/שלום/x",
+                false,
+            ),
+            (
+                corpus!("rtl_path.txt"),
+                "Bot",
+                "https://e.com/שלום/פתוח",
+                true,
+            ),
+            (
+                corpus!("bidi_controls.txt"),
+                "Bot",
+                "https://e.com/admin/",
+                true,
+            ),
+            (
+                corpus!("emoji_user_agent.txt"),
+                "bot",
+                "https://e.com/",
+                true,
+            ),
+            (corpus!("bom.txt"), "Bot", "https://e.com/private/", false),
+            (
+                corpus!("fullwidth_colon.txt"),
+                "Bot",
+                "https://e.com/",
+                true,
+            ),
+        ];
+        for &(robots, agent, url, allowed) in cases {
+            assert_eq!(
+                m.is_allowed(robots, agent, url),
+                allowed,
+                "{robots:?} {url}"
+            );
+        }
+
+        let clean = ParseStats {
+            total_lines: 3,
+            directives: 2,
+            ..Default::default()
+        };
+        assert_eq!(parse_stats(corpus!("emoji_path.txt")), clean);
+        assert_eq!(parse_stats(corpus!("bom.txt")), clean);
+        assert_eq!(
+            parse_stats(corpus!("bidi_controls.txt")).invisible_char_lines,
+            1
+        );
+        assert_eq!(
+            parse_stats(corpus!("emoji_user_agent.txt")).unmatchable_user_agents,
+            1
+        );
+        assert_eq!(
+            parse_stats(corpus!("fullwidth_colon.txt")).malformed_lines,
+            1
+        );
+
+        let latin1 = String::from_utf8_lossy(include_bytes!("../testdata/i18n/latin1.txt"));
+        assert!(m.is_allowed(&latin1, "Bot", "https://e.com/caf%E9"));
+        assert_eq!(parse_stats(&latin1).invalid_utf8_lines, 1);
+
+        let path = "🚀".repeat(5000);
+        let oversized = format!("User-agent: *\nDisallow: /{path}\n");
+        assert!(!m.is_allowed(&oversized, "Bot", &format!("https://e.com/{path}")));
+        assert_eq!(parse_stats(&oversized).truncated_lines, 1);
+    }
+
+    #[test]
+    fn test_create_free_cycles() {
+        for _ in 0..10_000 {
+            let m = RobotsMatcher::new();
+            assert!(m.is_allowed("User-agent: *\nDisallow: /x\n", "Bot", "/"));
+        }
+        let mut m = RobotsMatcher::new();
+        m.release();
+        // Neither a second release nor Drop may free the C++ object again.
+        m.release();
+        assert_eq!(m.matching_line(), 0);
+    }
+
+    /// Creates a million matchers and checks that resident memory stays
+    /// flat. Run with `cargo test -- --ignored`, optionally under valgrind
+    /// or with a sanitizer-instrumented librobots.
+    #[test]
+    #[ignore]
+    #[cfg(target_os = "linux")]
+    fn stress_create_free_does_not_grow_rss() {
+        fn rss_pages() -> usize {
+            let statm = std::fs::read_to_string("/proc/self/statm").unwrap();
+            statm.split_whitespace().nth(1).unwrap().parse().unwrap()
+        }
+        let robots = "User-agent: *\nDisallow: /private/\nCrawl-delay: 1\n";
+        let cycle = || {
+            for _ in 0..100_000 {
+                let m = RobotsMatcher::new();
+                m.is_allowed(robots, "Googlebot", "https://example.com/private/x");
+            }
+        };
+        cycle();
+        let before = rss_pages();
+        for _ in 0..10 {
+            cycle();
+        }
+        let grown = rss_pages().saturating_sub(before);
+        assert!(grown < 256, "RSS grew by {grown} pages over 1M matchers");
     }
 }
@@ -0,0 +1,395 @@
+//! Machine-readable warnings about a robots.txt.
+//!
+//! The parser tolerates many mistakes silently: it accepts misspelled keys,
+//! skips rules outside any group and cuts off long lines. [`lint`] reports
+//! each of them as a [`Diagnostic`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::lint::{lint, Code};
+//!
+//! let diagnostics = lint("Disallow: /early\nUser-agent: *\nDissalow: /tmp\n").unwrap();
+//! let codes: Vec<Code> = diagnostics.iter().map(|d| d.code).collect();
+//! assert_eq!(codes, [Code::RuleOutsideGroup, Code::MisspelledDirective]);
+//! for diagnostic in &diagnostics {
+//!     eprintln!("{diagnostic}");
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::fmt;
+use std::os::raw::c_int;
+
+use crate::security::MAX_ROBOTS_TXT_BYTES;
+use crate::{parse_robots_txt, symbols, ContentSignal, ParseHandler, RequestRate, RobotsError};
+
+/// Longest line the parser reads; the rest of the line is ignored.
+const MAX_LINE_BYTES: usize = 2083 * 8 - 1;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RawLineMetadata {
+    is_empty: bool,
+    has_comment: bool,
+    is_comment: bool,
+    has_directive: bool,
+    is_acceptable_typo: bool,
+    is_line_too_long: bool,
+    is_missing_colon_separator: bool,
+}
+
+pub(crate) type LineCallback = unsafe extern "C" fn(*mut c_void, c_int, *const RawLineMetadata);
+
+/// How much a diagnostic matters, least first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// Worth knowing, but crawlers read the line as intended.
+    Info,
+    /// Some crawlers read the line differently from others.
+    Warning,
+    /// No crawler applies the line as written.
+    Error,
+}
+
+/// The kind of issue a diagnostic reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+#[non_exhaustive]
+pub enum Code {
+    /// A key accepted only as a typo, such as `Dissalow`.
+    MisspelledDirective,
+    /// A directive without a `:` between key and value.
+    MissingColon,
+    /// A rule before the first `User-agent` line.
+    RuleOutsideGroup,
+    /// An Allow or Disallow pattern starting with neither `/` nor `*`.
+    MissingLeadingSlash,
+    /// A line cut off at the parser's maximum line length.
+    LineTooLong,
+    /// A file larger than the 500 KiB crawlers must parse.
+    FileTooLarge,
+    /// A line that is neither a directive, a comment nor empty.
+    MalformedLine,
+    /// A directive with a key the parser does not know.
+    UnknownDirective,
+}
+
+impl Code {
+    /// Returns the code as a stable identifier, e.g. `misspelled-directive`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Code::MisspelledDirective => "misspelled-directive",
+            Code::MissingColon => "missing-colon",
+            Code::RuleOutsideGroup => "rule-outside-group",
+            Code::MissingLeadingSlash => "missing-leading-slash",
+            Code::LineTooLong => "line-too-long",
+            Code::FileTooLarge => "file-too-large",
+            Code::MalformedLine => "malformed-line",
+            Code::UnknownDirective => "unknown-directive",
+        }
+    }
+
+    /// Returns the severity of diagnostics with this code.
+    pub fn severity(self) -> Severity {
+        match self {
+            Code::RuleOutsideGroup | Code::MissingLeadingSlash => Severity::Error,
+            Code::UnknownDirective => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+/// An issue found by [`lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    /// 1-based line number.
+    pub line: i32,
+    pub severity: Severity,
+    pub code: Code,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "line {}: {severity}: {} [{}]",
+            self.line,
+            self.message,
+            self.code.as_str()
+        )
+    }
+}
+
+/// Checks `robots_txt` and returns its diagnostics, ordered by line.
+///
+/// Fails with [`RobotsError::Unsupported`] if the library is too old to
+/// report how it treated each line.
+pub fn lint(robots_txt: &str) -> Result<Vec<Diagnostic>, RobotsError> {
+    let parse_lines = symbols::robots_parse_lines().ok_or(RobotsError::Unsupported {
+        symbol: "robots_parse_lines",
+    })?;
+    let mut collector = Collector::default();
+    parse_robots_txt(robots_txt, &mut collector)?;
+    let mut lines: Vec<(c_int, RawLineMetadata)> = Vec::new();
+    unsafe {
+        parse_lines(
+            robots_txt.as_ptr().cast(),
+            robots_txt.len(),
+            push_line,
+            (&mut lines as *mut Vec<(c_int, RawLineMetadata)>).cast(),
+        );
+    }
+
+    let texts = split_lines(robots_txt);
+    let mut diagnostics = collector.diagnostics;
+    for (line, metadata) in lines {
+        let mut add = |code: Code, message: String| {
+            diagnostics.push(Diagnostic {
+                line,
+                severity: code.severity(),
+                code,
+                message,
+            })
+        };
+        let directive = collector.directives.get(&line).copied();
+        let text = texts.get(line as usize - 1).map_or("", |(_, text)| text);
+        if metadata.is_acceptable_typo {
+            let key = key_of(text);
+            add(
+                Code::MisspelledDirective,
+                match directive {
+                    Some(directive) => format!(
+                        "\"{key}\" is a misspelling of {directive}; Google accepts it, other crawlers may ignore the line"
+                    ),
+                    None => format!("\"{key}\" is a misspelling"),
+                },
+            );
+        }
+        if metadata.has_directive && metadata.is_missing_colon_separator {
+            add(
+                Code::MissingColon,
+                format!(
+                    "{} has no colon; Google accepts it, other crawlers may ignore the line",
+                    directive.unwrap_or("directive")
+                ),
+            );
+        }
+        if metadata.is_line_too_long {
+            add(
+                Code::LineTooLong,
+                format!("line is longer than {MAX_LINE_BYTES} bytes; the rest is ignored"),
+            );
+        }
+        if !metadata.has_directive && !metadata.is_empty && !metadata.is_comment {
+            add(
+                Code::MalformedLine,
+                "line is not a directive and is ignored".to_string(),
+            );
+        }
+    }
+
+    if robots_txt.len() > MAX_ROBOTS_TXT_BYTES {
+        let line = texts
+            .iter()
+            .position(|(end, _)| *end > MAX_ROBOTS_TXT_BYTES)
+            .map_or(texts.len(), |i| i + 1);
+        diagnostics.push(Diagnostic {
+            line: line as i32,
+            severity: Code::FileTooLarge.severity(),
+            code: Code::FileTooLarge,
+            message: format!(
+                "file is {} bytes; crawlers may ignore everything after the first {MAX_ROBOTS_TXT_BYTES}",
+                robots_txt.len()
+            ),
+        });
+    }
+    diagnostics.sort_by_key(|d| d.line);
+    Ok(diagnostics)
+}
+
+unsafe extern "C" fn push_line(
+    context: *mut c_void,
+    line: c_int,
+    metadata: *const RawLineMetadata,
+) {
+    let lines = &mut *context.cast::<Vec<(c_int, RawLineMetadata)>>();
+    lines.push((line, *metadata));
+}
+
+/// Splits `text` into lines as the parser does, returning each line with
+/// the byte offset of its end.
+fn split_lines(text: &str) -> Vec<(usize, &str)> {
+    let bytes = text.as_bytes();
+    let mut start = bytes
+        .iter()
+        .zip(b"\xEF\xBB\xBF")
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut lines = Vec::new();
+    let mut last_was_cr = false;
+    for (i, &b) in bytes.iter().enumerate().skip(start) {
+        if b == b'\n' || b == b'\r' {
+            if !(i == start && last_was_cr && b == b'\n') {
+                lines.push((i, &text[start..i]));
+            }
+            start = i + 1;
+            last_was_cr = b == b'\r';
+        }
+    }
+    lines.push((text.len(), &text[start..]));
+    lines
+}
+
+/// Returns the key of a directive line, e.g. `Dissalow`.
+fn key_of(line: &str) -> &str {
+    let line = line.split('#').next().unwrap_or_default().trim();
+    let end = line
+        .find(|c: char| c == ':' || c.is_whitespace())
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+#[derive(Default)]
+struct Collector {
+    seen_agent: bool,
+    /// Canonical key of each directive line.
+    directives: HashMap<i32, &'static str>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Collector {
+    fn rule(&mut self, line: i32, directive: &'static str) {
+        self.directives.insert(line, directive);
+        if !self.seen_agent {
+            self.diagnostics.push(Diagnostic {
+                line,
+                severity: Code::RuleOutsideGroup.severity(),
+                code: Code::RuleOutsideGroup,
+                message: format!("{directive} before any User-agent line is ignored"),
+            });
+        }
+    }
+
+    fn pattern(&mut self, line: i32, directive: &'static str, value: &str) {
+        self.rule(line, directive);
+        if !value.is_empty() && !value.starts_with(['/', '*']) {
+            self.diagnostics.push(Diagnostic {
+                line,
+                severity: Code::MissingLeadingSlash.severity(),
+                code: Code::MissingLeadingSlash,
+                message: format!(
+                    "{directive} pattern \"{value}\" starts with neither / nor * and never matches"
+                ),
+            });
+        }
+    }
+}
+
+impl ParseHandler for Collector {
+    fn handle_user_agent(&mut self, line: i32, _value: &str) {
+        self.seen_agent = true;
+        self.directives.insert(line, "User-agent");
+    }
+
+    fn handle_allow(&mut self, line: i32, value: &str) {
+        self.pattern(line, "Allow", value);
+    }
+
+    fn handle_disallow(&mut self, line: i32, value: &str) {
+        self.pattern(line, "Disallow", value);
+    }
+
+    fn handle_sitemap(&mut self, line: i32, _value: &str) {
+        self.directives.insert(line, "Sitemap");
+    }
+
+    fn handle_crawl_delay(&mut self, line: i32, _seconds: f64) {
+        self.rule(line, "Crawl-delay");
+    }
+
+    fn handle_request_rate(&mut self, line: i32, _rate: RequestRate) {
+        self.rule(line, "Request-rate");
+    }
+
+    fn handle_content_signal(&mut self, line: i32, _signal: ContentSignal) {
+        self.rule(line, "Content-Signal");
+    }
+
+    fn handle_unknown_action(&mut self, line: i32, action: &str, _value: &str) {
+        self.diagnostics.push(Diagnostic {
+            line,
+            severity: Code::UnknownDirective.severity(),
+            code: Code::UnknownDirective,
+            message: format!("unknown directive \"{action}\" is ignored by the matcher"),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        let robots = "\u{feff}Disallow: /early\r\n\
+                      User-agent: *\r\n\
+                      Dissalow: /tmp # old\r\n\
+                      Disallow admin\r\n\
+                      Host: example.com\r\n\
+                      just some words\n";
+        let diagnostics = lint(robots).unwrap();
+        let found: Vec<(i32, Code)> = diagnostics.iter().map(|d| (d.line, d.code)).collect();
+        assert_eq!(
+            found,
+            [
+                (1, Code::RuleOutsideGroup),
+                (3, Code::MisspelledDirective),
+                (4, Code::MissingLeadingSlash),
+                (4, Code::MissingColon),
+                (5, Code::UnknownDirective),
+                (6, Code::MalformedLine),
+            ]
+        );
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "line 3: warning: \"Dissalow\" is a misspelling of Disallow; Google accepts it, \
+             other crawlers may ignore the line [misspelled-directive]"
+        );
+        assert_eq!(diagnostics[2].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_lint_sizes() {
+        let robots = format!(
+            "User-agent: *\nDisallow: /{}\n{}\n",
+            "a".repeat(MAX_LINE_BYTES),
+            "#".repeat(MAX_ROBOTS_TXT_BYTES)
+        );
+        let found: Vec<(i32, Code)> = lint(&robots)
+            .unwrap()
+            .iter()
+            .map(|d| (d.line, d.code))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (2, Code::LineTooLong),
+                (3, Code::LineTooLong),
+                (3, Code::FileTooLarge)
+            ]
+        );
+        assert!(lint("").unwrap().is_empty());
+    }
+}
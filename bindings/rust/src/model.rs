@@ -0,0 +1,207 @@
+//! Structured view of the groups and rules in a robots.txt.
+//!
+//! Groups are formed the way the matcher forms them: consecutive
+//! `User-agent` lines share a group, and a `User-agent` line after an
+//! `Allow` or `Disallow` starts a new one. Rules before the first
+//! `User-agent` line belong to no group and are left out, as the matcher
+//! ignores them.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::model::{RobotsDocument, Verb};
+//!
+//! let doc = RobotsDocument::parse("User-agent: a\nUser-agent: b\nDisallow: /x\n").unwrap();
+//! assert_eq!(doc.groups[0].agents, ["a", "b"]);
+//! assert_eq!(doc.groups[0].rules[0].verb, Verb::Disallow);
+//! assert_eq!(doc.groups[0].rules[0].line, 3);
+//! ```
+
+use std::os::raw::{c_char, c_int};
+
+use crate::{symbols, RobotsError, RobotsTxt};
+
+// Values of robots_directive_kind_t.
+const USER_AGENT: c_int = 0;
+pub(crate) const ALLOW: c_int = 1;
+const DISALLOW: c_int = 2;
+
+#[repr(C)]
+pub(crate) struct RawDirective {
+    kind: c_int,
+    line: c_int,
+    value: *const c_char,
+    value_len: usize,
+    // Key of an unknown directive; not needed for groups.
+    #[allow(dead_code)]
+    key: *const c_char,
+    #[allow(dead_code)]
+    key_len: usize,
+}
+
+impl Default for RawDirective {
+    fn default() -> Self {
+        RawDirective {
+            kind: -1,
+            line: 0,
+            value: std::ptr::null(),
+            value_len: 0,
+            key: std::ptr::null(),
+            key_len: 0,
+        }
+    }
+}
+
+impl RawDirective {
+    /// Copies the value out of the parsed robots.txt.
+    ///
+    /// # Safety
+    ///
+    /// The robots.txt the directive was read from must still be alive.
+    unsafe fn value(&self) -> String {
+        if self.value.is_null() {
+            return String::new();
+        }
+        // Lines are split on bytes, so the value may not be valid UTF-8.
+        let bytes = std::slice::from_raw_parts(self.value.cast::<u8>(), self.value_len);
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Whether a rule allows or disallows the paths it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verb {
+    Allow,
+    Disallow,
+}
+
+/// An `Allow` or `Disallow` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub verb: Verb,
+    /// The pattern as the matcher sees it: percent-escaped, with the
+    /// original `*` and `$` wildcards.
+    pub pattern: String,
+    /// Line number in the robots.txt.
+    pub line: i32,
+}
+
+/// User-agent lines and the rules that apply to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    /// The `User-agent` values as written, e.g. `*` or `Googlebot/2.1`.
+    pub agents: Vec<String>,
+    /// Rules in file order.
+    pub rules: Vec<Rule>,
+}
+
+/// The groups of a robots.txt, in file order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotsDocument {
+    pub groups: Vec<Group>,
+}
+
+impl RobotsDocument {
+    /// Parses `robots_txt` into groups.
+    ///
+    /// Fails with [`RobotsError::Unsupported`] if the library cannot list
+    /// the directives of a parsed robots.txt.
+    pub fn parse(robots_txt: &str) -> Result<RobotsDocument, RobotsError> {
+        RobotsDocument::from_parsed(&RobotsTxt::parse(robots_txt))
+    }
+
+    /// Same as [`parse`](Self::parse), for an already parsed robots.txt.
+    pub fn from_parsed(robots: &RobotsTxt) -> Result<RobotsDocument, RobotsError> {
+        let count = symbols::robots_txt_directive_count().ok_or(RobotsError::Unsupported {
+            symbol: "robots_txt_directive_count",
+        })?;
+        let get = symbols::robots_txt_get_directive().ok_or(RobotsError::Unsupported {
+            symbol: "robots_txt_get_directive",
+        })?;
+        let ptr = robots.ptr.ok_or(RobotsError::Unsupported {
+            symbol: "robots_txt_parse",
+        })?;
+        let ptr = ptr.as_ptr().cast_const();
+
+        let mut doc = RobotsDocument::default();
+        let mut in_rules = true;
+        for i in 0..unsafe { count(ptr) } {
+            let mut raw = RawDirective::default();
+            if !unsafe { get(ptr, i, &mut raw) } {
+                continue;
+            }
+            let verb = match raw.kind {
+                USER_AGENT => {
+                    if in_rules {
+                        doc.groups.push(Group {
+                            agents: Vec::new(),
+                            rules: Vec::new(),
+                        });
+                        in_rules = false;
+                    }
+                    let group = doc.groups.last_mut().expect("group was just added");
+                    group.agents.push(unsafe { raw.value() });
+                    continue;
+                }
+                ALLOW => Verb::Allow,
+                DISALLOW => Verb::Disallow,
+                _ => continue,
+            };
+            in_rules = true;
+            if let Some(group) = doc.groups.last_mut() {
+                group.rules.push(Rule {
+                    verb,
+                    pattern: unsafe { raw.value() },
+                    line: raw.line,
+                });
+            }
+        }
+        Ok(doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups() {
+        if crate::missing_symbols().contains(&"robots_txt_get_directive") {
+            return;
+        }
+        let robots = "Disallow: /orphan\n\
+                      User-agent: a\nUser-agent: B\nDisallow: /x\nCrawl-delay: 5\nAllow: /x/y*$\n\
+                      User-agent: *\nDisallow: /caf\u{e9}\n\
+                      Sitemap: https://example.com/s.xml\n";
+        let doc = RobotsDocument::parse(robots).unwrap();
+        assert_eq!(
+            doc.groups,
+            [
+                Group {
+                    agents: vec!["a".into(), "B".into()],
+                    rules: vec![
+                        Rule {
+                            verb: Verb::Disallow,
+                            pattern: "/x".into(),
+                            line: 4,
+                        },
+                        Rule {
+                            verb: Verb::Allow,
+                            pattern: "/x/y*$".into(),
+                            line: 6,
+                        },
+                    ],
+                },
+                Group {
+                    agents: vec!["*".into()],
+                    rules: vec![Rule {
+                        verb: Verb::Disallow,
+                        pattern: "/caf%C3%A9".into(),
+                        line: 8,
+                    }],
+                },
+            ]
+        );
+        assert!(RobotsDocument::parse("").unwrap().groups.is_empty());
+    }
+}
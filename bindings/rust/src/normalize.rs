@@ -0,0 +1,252 @@
+//! URL normalization and de-duplication consistent with robots.txt matching.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::normalize::{dedupe_urls, dedupe_urls_with, CleanParam};
+//!
+//! let urls = ["HTTP://Example.com:80/a#top", "http://example.com/a", "http://example.com/%7Euser"];
+//! assert_eq!(dedupe_urls(urls), ["http://example.com/a", "http://example.com/~user"]);
+//!
+//! let clean = [CleanParam::parse("sid&ref /forum/").unwrap()];
+//! let urls = ["https://example.com/forum/t?id=1&sid=9", "https://example.com/forum/t?id=1"];
+//! assert_eq!(dedupe_urls_with(urls, &clean), ["https://example.com/forum/t?id=1"]);
+//! ```
+
+use std::collections::HashSet;
+
+/// A `Clean-param` rule: query parameters that do not change the page
+/// content under a path prefix.
+///
+/// Only plain path prefixes are supported; `*` in the prefix is matched
+/// literally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanParam {
+    pub params: Vec<String>,
+    pub path_prefix: String,
+}
+
+impl CleanParam {
+    /// Parses the value of a `Clean-param:` line, e.g. `ref&sid /forum/`.
+    /// Without a prefix the rule applies to every path.
+    pub fn parse(value: &str) -> Option<CleanParam> {
+        let mut parts = value.split_whitespace();
+        let params: Vec<String> = parts
+            .next()?
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+        if params.is_empty() {
+            return None;
+        }
+        let path_prefix = parts.next().unwrap_or("/").to_string();
+        Some(CleanParam {
+            params,
+            path_prefix,
+        })
+    }
+
+    fn applies_to(&self, path: &str) -> bool {
+        path.starts_with(&self.path_prefix)
+    }
+}
+
+/// Normalizes a URL the way robots.txt matching sees it.
+///
+/// The scheme and host are lowercased, userinfo, default ports and the
+/// fragment are dropped, an empty path becomes `/`, percent-escapes of
+/// unreserved characters are decoded, remaining escapes are uppercased and
+/// non-ASCII bytes are percent-encoded. URLs without a `scheme://` prefix
+/// are treated as a path.
+pub fn normalize_url(url: &str) -> String {
+    normalize_with(url, &[])
+}
+
+/// Returns the distinct URLs from `urls`, normalized with
+/// [`normalize_url`], in order of first occurrence.
+pub fn dedupe_urls<I, S>(urls: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    dedupe_urls_with(urls, &[])
+}
+
+/// Same as [`dedupe_urls`], but also removes query parameters listed in
+/// matching `Clean-param` rules before comparing.
+pub fn dedupe_urls_with<I, S>(urls: I, clean_params: &[CleanParam]) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    for url in urls {
+        let normalized = normalize_with(url.as_ref(), clean_params);
+        if seen.insert(normalized.clone()) {
+            result.push(normalized);
+        }
+    }
+    result
+}
+
+fn normalize_with(url: &str, clean_params: &[CleanParam]) -> String {
+    let url = url.split_once('#').map_or(url, |(u, _)| u);
+    let (origin, path_and_query) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let end = rest.find(['/', '?']).unwrap_or(rest.len());
+            (normalize_origin(scheme, &rest[..end]), &rest[end..])
+        }
+        None => (String::new(), url),
+    };
+
+    let (path, query) = match path_and_query.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path_and_query, None),
+    };
+    let mut result = origin;
+    if !path.starts_with('/') {
+        result.push('/');
+    }
+    let path_start = result.len();
+    escape_into(path, &mut result);
+
+    if let Some(query) = query {
+        let path = result[path_start..].to_string();
+        let kept: Vec<&str> = query
+            .split('&')
+            .filter(|pair| {
+                let key = pair.split_once('=').map_or(*pair, |(k, _)| k);
+                !clean_params
+                    .iter()
+                    .any(|c| c.applies_to(&path) && c.params.iter().any(|p| p == key))
+            })
+            .collect();
+        if !kept.is_empty() {
+            result.push('?');
+            escape_into(&kept.join("&"), &mut result);
+        }
+    }
+    result
+}
+
+fn normalize_origin(scheme: &str, authority: &str) -> String {
+    let scheme = scheme.to_ascii_lowercase();
+    let host_port = normalize_authority(&scheme, authority);
+    format!("{scheme}://{host_port}")
+}
+
+/// Lowercases `authority` and drops the userinfo and an empty or default
+/// port for `scheme` (lowercase). IPv6 literals keep their brackets.
+pub(crate) fn normalize_authority(scheme: &str, authority: &str) -> String {
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let mut host_port = host_port.to_ascii_lowercase();
+    let default_port = match scheme {
+        "http" => Some(80),
+        "https" => Some(443),
+        _ => None,
+    };
+    // The colons inside "[::1]" are part of the host, not a port separator.
+    let port_sep = host_port
+        .rfind(':')
+        .filter(|&i| !host_port[i..].contains(']'));
+    if let Some(i) = port_sep {
+        let port = &host_port[i + 1..];
+        if port.is_empty() || default_port.is_some_and(|d| port.parse() == Ok(d)) {
+            host_port.truncate(i);
+        }
+    }
+    host_port
+}
+
+/// Appends `s` with percent-escapes normalized, like the parser does for
+/// patterns, and unreserved characters decoded.
+fn escape_into(s: &str, out: &mut String) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                let decoded = hi << 4 | lo;
+                if decoded.is_ascii_alphanumeric() || matches!(decoded, b'-' | b'.' | b'_' | b'~') {
+                    out.push(decoded as char);
+                } else {
+                    out.push('%');
+                    out.push(HEX[usize::from(hi)] as char);
+                    out.push(HEX[usize::from(lo)] as char);
+                }
+                i += 3;
+                continue;
+            }
+        }
+        if b >= 0x80 {
+            out.push('%');
+            out.push(HEX[usize::from(b >> 4)] as char);
+            out.push(HEX[usize::from(b & 0xf)] as char);
+        } else {
+            out.push(b as char);
+        }
+        i += 1;
+    }
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("HTTPS://user@Example.COM:443?b=%2f#frag"),
+            "https://example.com/?b=%2F"
+        );
+        assert_eq!(
+            normalize_url("http://example.com:8080/%7e/caf\u{e9}"),
+            "http://example.com:8080/~/caf%C3%A9"
+        );
+        assert_eq!(normalize_url("/path?q"), "/path?q");
+    }
+
+    #[test]
+    fn test_normalize_ip_origins() {
+        assert_eq!(
+            normalize_url("http://192.0.2.1:8080/a"),
+            "http://192.0.2.1:8080/a"
+        );
+        assert_eq!(normalize_url("http://192.0.2.1:80/a"), "http://192.0.2.1/a");
+        assert_eq!(
+            normalize_url("HTTP://[2001:DB8::1]:080/a"),
+            "http://[2001:db8::1]/a"
+        );
+        assert_eq!(normalize_url("https://[::1]:80/"), "https://[::1]:80/");
+        assert_eq!(normalize_url("https://[::1]:/"), "https://[::1]/");
+        assert_eq!(normalize_url("ftp://[::1]/"), "ftp://[::1]/");
+    }
+
+    #[test]
+    fn test_dedupe_with_clean_param() {
+        let clean = [CleanParam::parse("sid&ref /forum/").unwrap()];
+        let urls = [
+            "https://example.com/forum/t?id=1&sid=9",
+            "https://example.com/forum/t?sid=3&id=1",
+            "https://example.com/forum/t?id=1",
+            "https://example.com/shop?sid=9",
+        ];
+        assert_eq!(
+            dedupe_urls_with(urls, &clean),
+            [
+                "https://example.com/forum/t?id=1",
+                "https://example.com/shop?sid=9"
+            ]
+        );
+        assert_eq!(dedupe_urls(urls).len(), 4);
+        assert_eq!(CleanParam::parse("  "), None);
+    }
+}
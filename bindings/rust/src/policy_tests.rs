@@ -0,0 +1,238 @@
+//! Assertions about a robots.txt, for testing it in a site's CI.
+//!
+//! A [`Spec`] lists which agents must be allowed or disallowed which
+//! paths. Write it in the line format of [`Spec::parse`], or, with the
+//! `serde` feature, deserialize it from TOML or YAML.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::policy_tests::{run, Spec};
+//!
+//! let spec = Spec::parse("Googlebot allowed /products/shoes\nGPTBot disallowed /\n").unwrap();
+//! let robots_txt = "User-agent: GPTBot\nDisallow: /\n";
+//! let report = run(robots_txt, &spec);
+//! for failure in report.failures() {
+//!     eprintln!("{} (line {})", failure.assertion, failure.line);
+//! }
+//! assert!(report.passed());
+//! ```
+
+use std::fmt;
+
+use crate::RobotsMatcher;
+
+/// The decision an assertion expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Expectation {
+    Allowed,
+    Disallowed,
+}
+
+/// One expected decision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assertion {
+    pub user_agent: String,
+    /// A path such as `/products/shoes`, or a full URL. Wildcards are not
+    /// supported: [`Spec::parse`] rejects paths with `*` or `$`, and
+    /// [`run`] fails such assertions from other sources.
+    pub path: String,
+    pub expect: Expectation,
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expect = match self.expect {
+            Expectation::Allowed => "allowed",
+            Expectation::Disallowed => "disallowed",
+        };
+        write!(f, "{} must be {expect} {}", self.user_agent, self.path)
+    }
+}
+
+/// A list of assertions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spec {
+    pub assertions: Vec<Assertion>,
+}
+
+/// A line of a spec that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+impl Spec {
+    /// Parses one assertion per line, `<user-agent> allowed|disallowed
+    /// <path>`. Blank lines and lines starting with `#` are skipped.
+    ///
+    /// Paths with `*` or `$` are rejected: checked literally, an assertion
+    /// like `Googlebot allowed /products/*` would pass even if every real
+    /// product page were disallowed.
+    pub fn parse(text: &str) -> Result<Spec, SpecError> {
+        let mut assertions = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| SpecError {
+                line: i + 1,
+                message,
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [user_agent, expect, path] = fields[..] else {
+                return Err(error(format!(
+                    "expected \"<user-agent> allowed|disallowed <path>\", got \"{line}\""
+                )));
+            };
+            let expect = match expect.to_ascii_lowercase().as_str() {
+                "allowed" => Expectation::Allowed,
+                "disallowed" => Expectation::Disallowed,
+                _ => {
+                    return Err(error(format!(
+                        "expected \"allowed\" or \"disallowed\", got \"{expect}\""
+                    )))
+                }
+            };
+            if has_wildcard(path) {
+                return Err(error(format!(
+                    "wildcards are not supported in paths, got \"{path}\"; list concrete paths such as /products/shoes"
+                )));
+            }
+            assertions.push(Assertion {
+                user_agent: user_agent.to_string(),
+                path: path.to_string(),
+                expect,
+            });
+        }
+        Ok(Spec { assertions })
+    }
+}
+
+/// The result of one assertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub assertion: Assertion,
+    pub passed: bool,
+    /// Line of the robots.txt rule that decided the check, or 0 if none
+    /// matched.
+    pub line: i32,
+}
+
+/// Results of [`run`], in spec order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyReport {
+    pub outcomes: Vec<Outcome>,
+}
+
+impl PolicyReport {
+    /// Returns true if every assertion passed.
+    pub fn passed(&self) -> bool {
+        self.outcomes.iter().all(|o| o.passed)
+    }
+
+    /// Returns the assertions that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &Outcome> {
+        self.outcomes.iter().filter(|o| !o.passed)
+    }
+}
+
+/// Checks every assertion in `spec` against `robots_txt`.
+///
+/// Assertions with a `*` or `$` in the path, which [`Spec::parse`] rejects
+/// but a deserialized spec may contain, fail with line 0.
+pub fn run(robots_txt: &str, spec: &Spec) -> PolicyReport {
+    let matcher = RobotsMatcher::new();
+    let outcomes = spec
+        .assertions
+        .iter()
+        .map(|assertion| {
+            if has_wildcard(&assertion.path) {
+                return Outcome {
+                    assertion: assertion.clone(),
+                    passed: false,
+                    line: 0,
+                };
+            }
+            let result = matcher.check(robots_txt, &assertion.user_agent, &assertion.path);
+            Outcome {
+                assertion: assertion.clone(),
+                passed: result.allowed == (assertion.expect == Expectation::Allowed),
+                line: result.line,
+            }
+        })
+        .collect();
+    PolicyReport { outcomes }
+}
+
+fn has_wildcard(path: &str) -> bool {
+    path.contains(['*', '$'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let spec = Spec::parse(
+            "# site policy\n\
+             Googlebot allowed /products/shoes\n\
+             \n\
+             GPTBot  Disallowed  /\n\
+             GPTBot allowed /public\n",
+        )
+        .unwrap();
+        assert_eq!(spec.assertions.len(), 3);
+
+        let robots = "User-agent: GPTBot\nDisallow: /\n\nUser-agent: *\nDisallow: /products/\n";
+        let report = run(robots, &spec);
+        assert!(!report.passed());
+        let failures: Vec<_> = report.failures().collect();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(
+            failures[0].assertion.to_string(),
+            "Googlebot must be allowed /products/shoes"
+        );
+        assert_eq!(failures[0].line, 5);
+        assert_eq!(failures[1].line, 2);
+        assert!(report.outcomes[1].passed);
+    }
+
+    #[test]
+    fn test_spec_errors() {
+        let err = Spec::parse("Googlebot allowed /\nGPTBot blocked /\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(Spec::parse("Googlebot /").is_err());
+
+        let err = Spec::parse("Googlebot allowed /products/*\n").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("wildcards"));
+        assert!(Spec::parse("Googlebot allowed /index.html$").is_err());
+
+        let spec = Spec {
+            assertions: vec![Assertion {
+                user_agent: "Googlebot".into(),
+                path: "/products/*".into(),
+                expect: Expectation::Allowed,
+            }],
+        };
+        let report = run("User-agent: *\nDisallow: /products/shoes\n", &spec);
+        assert!(!report.passed());
+    }
+}
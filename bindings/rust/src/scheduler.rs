@@ -0,0 +1,286 @@
+//! Per-host request spacing from Crawl-delay and Request-rate.
+//!
+//! [`Scheduler::acquire`] waits until the host's next request slot and
+//! reserves the one after it, so concurrent tasks for the same host are
+//! spaced by the host's delay. Waiting goes through a [`Sleeper`], so the
+//! scheduler works with any async runtime.
+//!
+//! # Example
+//!
+//! With tokio, pass `tokio::time::sleep` as the sleeper; with async-std,
+//! `async_std::task::sleep`; with smol, `smol::Timer::after`:
+//!
+//! ```no_run
+//! use robotstxt::scheduler::{Scheduler, Sleeper};
+//!
+//! async fn crawl(sleeper: impl Sleeper) {
+//!     let scheduler = Scheduler::new(sleeper);
+//!     scheduler.set_delay_from_robots("example.com", "User-agent: *\nCrawl-delay: 2\n", "MyBot");
+//!     for path in ["/a", "/b"] {
+//!         let _permit = scheduler.acquire("example.com").await;
+//!         // fetch https://example.com{path}
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+use crate::RobotsMatcher;
+
+/// Longest delay between requests to a host. Longer delays, whether set
+/// directly or read from a robots.txt, are cut to this.
+pub const MAX_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Waits for a duration on the caller's async runtime.
+///
+/// Implemented for functions like `tokio::time::sleep`,
+/// `async_std::task::sleep` or `async_io::Timer::after` (used by smol);
+/// the output of the returned future is ignored.
+pub trait Sleeper {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send;
+}
+
+impl<F, Fut> Sleeper for F
+where
+    F: Fn(Duration) -> Fut,
+    Fut: Future + Send,
+{
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send {
+        let sleep = self(duration);
+        async move {
+            sleep.await;
+        }
+    }
+}
+
+/// A reserved request slot for a host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permit {
+    host: String,
+    waited: Duration,
+}
+
+impl Permit {
+    /// The host the slot is for, in lowercase.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// How long [`Scheduler::acquire`] waited for the slot.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+#[derive(Default)]
+struct HostState {
+    delay: Option<Duration>,
+    next: Option<Instant>,
+}
+
+/// Spaces requests per host.
+///
+/// Hosts without a delay set use the default delay, zero unless changed
+/// with [`with_default_delay`](Self::with_default_delay).
+pub struct Scheduler<S, C = SystemClock> {
+    sleeper: S,
+    clock: C,
+    default_delay: Duration,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl<S> Scheduler<S> {
+    /// Creates a scheduler that waits with `sleeper`.
+    pub fn new(sleeper: S) -> Self {
+        Scheduler {
+            sleeper,
+            clock: SystemClock,
+            default_delay: Duration::ZERO,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<S, C: Clock> Scheduler<S, C> {
+    /// Sets the delay for hosts without one of their own, at most
+    /// [`MAX_DELAY`].
+    pub fn with_default_delay(mut self, delay: Duration) -> Self {
+        self.default_delay = delay.min(MAX_DELAY);
+        self
+    }
+
+    /// Uses `clock` to measure the spacing. The sleeper should wait on the
+    /// same clock.
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> Scheduler<S, C2> {
+        Scheduler {
+            sleeper: self.sleeper,
+            clock,
+            default_delay: self.default_delay,
+            hosts: self.hosts,
+        }
+    }
+
+    /// Sets the delay between requests to `host`, at most [`MAX_DELAY`].
+    pub fn set_delay(&self, host: &str, delay: Duration) {
+        self.lock()
+            .entry(host.to_ascii_lowercase())
+            .or_default()
+            .delay = Some(delay.min(MAX_DELAY));
+    }
+
+    /// Sets the delay for `host` from the Crawl-delay, or else the
+    /// Request-rate, that `robots_txt` gives `user_agent`, and returns it.
+    /// Delays longer than [`MAX_DELAY`] are cut to it.
+    ///
+    /// Returns None and leaves the host's delay unchanged if the robots.txt
+    /// sets neither, or sets a negative or non-finite value.
+    pub fn set_delay_from_robots(
+        &self,
+        host: &str,
+        robots_txt: &str,
+        user_agent: &str,
+    ) -> Option<Duration> {
+        let matcher = RobotsMatcher::new();
+        let secs = matcher
+            .crawl_delay_for(robots_txt, user_agent)
+            .or_else(|| {
+                matcher
+                    .request_rate_for(robots_txt, user_agent)?
+                    .interval_secs()
+            })?;
+        let delay = match Duration::try_from_secs_f64(secs) {
+            Ok(delay) => delay.min(MAX_DELAY),
+            Err(_) if secs.is_finite() && secs > 0.0 => MAX_DELAY,
+            Err(_) => return None,
+        };
+        self.set_delay(host, delay);
+        Some(delay)
+    }
+
+    /// Returns the delay used for `host`.
+    pub fn delay(&self, host: &str) -> Duration {
+        self.lock()
+            .get(&host.to_ascii_lowercase())
+            .and_then(|state| state.delay)
+            .unwrap_or(self.default_delay)
+    }
+
+    /// Reserves the next slot for `host` and returns how long to wait for
+    /// it, for callers that wait without a [`Sleeper`].
+    pub fn reserve(&self, host: &str) -> Duration {
+        let now = self.clock.now();
+        let mut hosts = self.lock();
+        let state = hosts.entry(host.to_ascii_lowercase()).or_default();
+        let slot = state.next.map_or(now, |next| next.max(now));
+        let delay = state.delay.unwrap_or(self.default_delay);
+        // Far enough in the future, Instant overflows; hold the host at the
+        // latest slot that fits.
+        state.next = Some(
+            slot.checked_add(delay)
+                .or_else(|| now.checked_add(MAX_DELAY))
+                .unwrap_or(slot),
+        );
+        slot - now
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, HostState>> {
+        self.hosts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<S: Sleeper, C: Clock> Scheduler<S, C> {
+    /// Waits until a request to `host` is allowed and returns its permit.
+    pub async fn acquire(&self, host: &str) -> Permit {
+        let waited = self.reserve(host);
+        if !waited.is_zero() {
+            self.sleeper.sleep(waited).await;
+        }
+        Permit {
+            host: host.to_ascii_lowercase(),
+            waited,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Waker};
+
+    fn ready<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is pending"),
+        }
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let clock = ManualClock::new();
+        let slept = Arc::new(Mutex::new(Vec::new()));
+        let sleeper = {
+            let clock = clock.clone();
+            let slept = Arc::clone(&slept);
+            move |duration: Duration| {
+                slept.lock().unwrap().push(duration);
+                clock.advance(duration);
+                async {}
+            }
+        };
+        let scheduler = Scheduler::new(sleeper)
+            .with_default_delay(Duration::from_secs(1))
+            .with_clock(clock.clone());
+        let robots = "User-agent: *\nRequest-rate: 1/4\nDisallow: /x\n\n\
+                      User-agent: FooBot\nCrawl-delay: 2\nDisallow: /y\n";
+        assert_eq!(
+            scheduler.set_delay_from_robots("A.com", robots, "FooBot"),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            scheduler.set_delay_from_robots("b.com", robots, "BarBot"),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(scheduler.set_delay_from_robots("c.com", "", "FooBot"), None);
+        assert_eq!(scheduler.delay("c.com"), Duration::from_secs(1));
+
+        assert_eq!(ready(scheduler.acquire("a.com")).waited(), Duration::ZERO);
+        assert_eq!(ready(scheduler.acquire("b.com")).waited(), Duration::ZERO);
+        let permit = ready(scheduler.acquire("a.com"));
+        assert_eq!(permit.host(), "a.com");
+        assert_eq!(permit.waited(), Duration::from_secs(2));
+        // b.com's slot at 4s is 2s away after waiting for a.com.
+        assert_eq!(scheduler.reserve("b.com"), Duration::from_secs(2));
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(ready(scheduler.acquire("a.com")).waited(), Duration::ZERO);
+        assert_eq!(*slept.lock().unwrap(), [Duration::from_secs(2)]);
+    }
+
+    #[test]
+    fn test_huge_crawl_delay() {
+        let clock = ManualClock::new();
+        let scheduler = Scheduler::new(|_: Duration| async {}).with_clock(clock.clone());
+        for (host, delay) in [("a.com", "1e19"), ("b.com", "1e18")] {
+            let robots = format!("User-agent: *\nCrawl-delay: {delay}\n");
+            let set = scheduler.set_delay_from_robots(host, &robots, "FooBot");
+            assert_eq!(set, Some(MAX_DELAY));
+            assert_eq!(scheduler.delay(host), MAX_DELAY);
+            assert_eq!(scheduler.reserve(host), Duration::ZERO);
+            for i in 1..5 {
+                assert_eq!(scheduler.reserve(host), MAX_DELAY * i);
+            }
+        }
+        scheduler.set_delay("d.com", Duration::MAX);
+        assert_eq!(scheduler.delay("d.com"), MAX_DELAY);
+        assert_eq!(ready(scheduler.acquire("d.com")).waited(), Duration::ZERO);
+    }
+}
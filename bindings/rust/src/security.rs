@@ -0,0 +1,104 @@
+//! Preset bundles of the options that trade coverage for caution.
+//!
+//! [`SecurityProfile::Paranoid`] configures a [`RobotsMatcher`] or a
+//! [`RobotsCache`](crate::cache::RobotsCache) to refuse whatever the
+//! defaults would accept leniently.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::security::SecurityProfile;
+//! use robotstxt::RobotsMatcher;
+//!
+//! let matcher = RobotsMatcher::new();
+//! SecurityProfile::Paranoid.apply(&matcher).unwrap();
+//! // "Dissalow" is a typo the default parser accepts.
+//! assert!(matcher.is_allowed("User-agent: *\nDissalow: /\n", "MyBot", "https://example.com/"));
+//! assert!(!matcher.is_allowed("User-agent: *\n", "MyBot", "ftp://example.com/"));
+//! ```
+
+use crate::{RobotsError, RobotsMatcher, UrlLengthPolicy, UrlLimit};
+
+/// Bytes of a robots.txt parsed under [`SecurityProfile::Paranoid`], the
+/// minimum RFC 9309 section 2.5 requires crawlers to parse. The rest is
+/// ignored.
+pub const MAX_ROBOTS_TXT_BYTES: usize = 500 * 1024;
+
+/// Longest URL checked under [`SecurityProfile::Paranoid`]; longer URLs
+/// are disallowed.
+pub const MAX_URL_BYTES: usize = 2083;
+
+/// A bundle of matcher and cache options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SecurityProfile {
+    /// The library defaults: lenient parsing as Googlebot does, any URL
+    /// scheme and no URL limit.
+    #[default]
+    Standard,
+    /// Deny by default:
+    ///
+    /// - strict parsing, ignoring typo variants of keys and lines without a
+    ///   colon ([`RobotsMatcher::set_strict`]);
+    /// - `http` and `https` URLs only;
+    /// - URLs longer than [`MAX_URL_BYTES`] rejected;
+    /// - in a cache, robots.txt bodies cut at [`MAX_ROBOTS_TXT_BYTES`],
+    ///   and every URL of an origin disallowed if its robots.txt cannot be
+    ///   parsed strictly. Unreachable servers are handled the same under
+    ///   every profile.
+    Paranoid,
+}
+
+impl SecurityProfile {
+    /// Returns the URL limit of the profile.
+    pub fn url_limit(self) -> Option<UrlLimit> {
+        match self {
+            SecurityProfile::Standard => None,
+            SecurityProfile::Paranoid => Some(UrlLimit {
+                max_len: MAX_URL_BYTES,
+                policy: UrlLengthPolicy::Reject,
+            }),
+        }
+    }
+
+    /// Sets the strict mode and URL limit of `matcher` for the profile.
+    ///
+    /// Fails with [`RobotsError::Unsupported`], leaving the matcher
+    /// unchanged, if the library is too old for strict mode.
+    pub fn apply(self, matcher: &RobotsMatcher) -> Result<(), RobotsError> {
+        let strict = self == SecurityProfile::Paranoid;
+        if strict != matcher.is_strict() {
+            matcher.set_strict(strict)?;
+        }
+        matcher.set_url_limit(self.url_limit());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let robots = "User-agent: *\nDissalow: /a\nDisallow /b\nDisallow: /c\n";
+        let matcher = RobotsMatcher::new();
+        assert!(!matcher.is_allowed(robots, "FooBot", "/a"));
+        assert!(!matcher.is_allowed(robots, "FooBot", "/b"));
+
+        SecurityProfile::Paranoid.apply(&matcher).unwrap();
+        assert!(matcher.is_strict());
+        assert!(matcher.is_allowed(robots, "FooBot", "/a"));
+        assert!(matcher.is_allowed(robots, "FooBot", "/b"));
+        assert!(!matcher.is_allowed(robots, "FooBot", "/c"));
+        assert_eq!(matcher.matching_line(), 4);
+        assert!(!matcher.is_allowed(robots, "FooBot", "ftp://x.com/"));
+        let long = format!("http://x.com/{}", "x".repeat(MAX_URL_BYTES));
+        assert!(!matcher.is_allowed(robots, "FooBot", &long));
+
+        SecurityProfile::Standard.apply(&matcher).unwrap();
+        assert!(!matcher.is_strict());
+        assert!(!matcher.is_allowed(robots, "FooBot", "/a"));
+        assert!(matcher.is_allowed(robots, "FooBot", &long));
+    }
+}
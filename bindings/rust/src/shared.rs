@@ -0,0 +1,213 @@
+//! Matchers that can be shared between threads.
+
+use std::sync::{Mutex, MutexGuard};
+
+use crate::RobotsMatcher;
+
+/// A [`RobotsMatcher`] behind a mutex, for sharing one matcher between
+/// threads (e.g. in an `Arc`).
+///
+/// `RobotsMatcher` keeps the state of the last check (matching line,
+/// crawl-delay, timing) inside the C++ object, so it is not `Sync`. This
+/// wrapper serializes checks; a thread that needs that state should read it
+/// in the same [`with`](Self::with) call as the check.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use robotstxt::SharedMatcher;
+///
+/// let matcher = Arc::new(SharedMatcher::new());
+/// let robots_txt = "User-agent: *\nDisallow: /admin/\n";
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let matcher = Arc::clone(&matcher);
+///         std::thread::spawn(move || {
+///             matcher.with(|m| {
+///                 let allowed = m.is_allowed(robots_txt, "Googlebot", "https://example.com/admin/x");
+///                 (allowed, m.matching_line())
+///             })
+///         })
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), (false, 2));
+/// }
+/// ```
+#[derive(Default)]
+pub struct SharedMatcher {
+    inner: Mutex<RobotsMatcher>,
+}
+
+impl SharedMatcher {
+    /// Creates a new shared matcher.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks if a URL is allowed for a single user-agent.
+    pub fn is_allowed(&self, robots_txt: &str, user_agent: &str, url: &str) -> bool {
+        self.lock().is_allowed(robots_txt, user_agent, url)
+    }
+
+    /// Runs `f` with exclusive access to the matcher.
+    pub fn with<R>(&self, f: impl FnOnce(&RobotsMatcher) -> R) -> R {
+        f(&self.lock())
+    }
+
+    /// Returns the wrapped matcher.
+    pub fn into_inner(self) -> RobotsMatcher {
+        self.inner.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Every check resets the matcher, so a panic in another thread's `with`
+    // cannot leave it in a state that affects later checks.
+    fn lock(&self) -> MutexGuard<'_, RobotsMatcher> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl From<RobotsMatcher> for SharedMatcher {
+    fn from(matcher: RobotsMatcher) -> Self {
+        Self {
+            inner: Mutex::new(matcher),
+        }
+    }
+}
+
+/// A pool of [`RobotsMatcher`]s for services that check URLs from many
+/// threads at once.
+///
+/// Unlike [`SharedMatcher`], checks run in parallel: each takes an idle
+/// matcher, or creates one if none is idle, and returns it afterwards.
+/// This avoids creating and freeing a C++ matcher per request. At most
+/// `max_idle` matchers are kept.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use robotstxt::MatcherPool;
+///
+/// let pool = Arc::new(MatcherPool::new());
+/// let robots_txt = "User-agent: *\nDisallow: /admin/\n";
+/// assert!(!pool.check(robots_txt, "Googlebot", "https://example.com/admin/x"));
+/// ```
+pub struct MatcherPool {
+    idle: Mutex<Vec<RobotsMatcher>>,
+    max_idle: usize,
+}
+
+impl MatcherPool {
+    /// Creates a pool keeping up to twice the available parallelism in
+    /// idle matchers.
+    pub fn new() -> Self {
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+        Self::with_max_idle(threads * 2)
+    }
+
+    /// Creates a pool keeping up to `max_idle` idle matchers.
+    pub fn with_max_idle(max_idle: usize) -> Self {
+        MatcherPool {
+            idle: Mutex::new(Vec::new()),
+            max_idle,
+        }
+    }
+
+    /// Checks if a URL is allowed for a single user-agent.
+    pub fn check(&self, robots_txt: &str, user_agent: &str, url: &str) -> bool {
+        self.with(|m| m.is_allowed(robots_txt, user_agent, url))
+    }
+
+    /// Runs `f` with a matcher from the pool. A URL limit, profiling or
+    /// strict mode set in `f` is reset before the matcher is reused.
+    pub fn with<R>(&self, f: impl FnOnce(&RobotsMatcher) -> R) -> R {
+        let matcher = self.lock().pop().unwrap_or_default();
+        let result = f(&matcher);
+        matcher.set_url_limit(None);
+        if matcher.profiling.get() {
+            matcher.set_profiling(false);
+        }
+        if matcher.strict.get() {
+            let _ = matcher.set_strict(false);
+        }
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            idle.push(matcher);
+        }
+        result
+    }
+
+    /// Returns the number of idle matchers.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<RobotsMatcher>> {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for MatcherPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UrlLengthPolicy, UrlLimit};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_concurrent_checks_keep_per_check_state() {
+        let matcher = Arc::new(SharedMatcher::new());
+        let robots = "User-agent: *\nDisallow: /a/\nAllow: /b/\nDisallow: /c/\n";
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let matcher = Arc::clone(&matcher);
+                std::thread::spawn(move || {
+                    let (path, line, allowed) =
+                        [("/a/x", 2, false), ("/b/x", 3, true), ("/c/x", 4, false)][i % 3];
+                    for _ in 0..500 {
+                        let result = matcher
+                            .with(|m| (m.is_allowed(robots, "Bot", path), m.matching_line()));
+                        assert_eq!(result, (allowed, line));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_matcher_pool() {
+        let pool = Arc::new(MatcherPool::with_max_idle(2));
+        let robots = "User-agent: *\nDisallow: /a/\n";
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let pool = Arc::clone(&pool);
+                std::thread::spawn(move || {
+                    for _ in 0..200 {
+                        assert!(!pool.check(robots, "Bot", "/a/x"));
+                        assert!(pool.check(robots, "Bot", "/b/x"));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(pool.idle() <= 2);
+
+        let limit = UrlLimit {
+            max_len: 1,
+            policy: UrlLengthPolicy::Reject,
+        };
+        assert!(!pool.with(|m| {
+            m.set_url_limit(Some(limit));
+            m.is_allowed(robots, "Bot", "/b/x")
+        }));
+        assert!(pool.check(robots, "Bot", "/b/x"));
+    }
+}
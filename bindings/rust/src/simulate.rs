@@ -0,0 +1,237 @@
+//! What-if analysis of a crawl policy over a URL frontier.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use robotstxt::simulate::simulate_crawl;
+//!
+//! let mut robots = HashMap::new();
+//! robots.insert("example.com".to_string(), "User-agent: *\nDisallow: /tmp/\nCrawl-delay: 2\n".to_string());
+//!
+//! let frontier = ["https://example.com/a", "https://example.com/tmp/b"];
+//! let report = simulate_crawl(frontier, "MyBot", |host| robots.get(host).cloned());
+//! println!("{} allowed, {:.0}s to crawl", report.allowed_urls(), report.schedule_secs());
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::normalize::normalize_authority;
+use crate::RobotsMatcher;
+
+/// A rule that blocked URLs on a host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedHotspot {
+    /// Line number of the blocking rule in the host's robots.txt.
+    pub line: i32,
+    /// Number of frontier URLs blocked by this rule.
+    pub blocked_urls: usize,
+}
+
+/// Simulation results for a single host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostReport {
+    /// Host (with port, if any) in lowercase.
+    pub host: String,
+    /// False if no robots.txt was available, in which case all URLs are allowed.
+    pub has_robots: bool,
+    pub allowed_urls: usize,
+    pub blocked_urls: usize,
+    /// Seconds between requests, from Crawl-delay or else Request-rate.
+    pub delay_secs: Option<f64>,
+    /// Time needed to fetch all allowed URLs sequentially at `delay_secs`.
+    pub schedule_secs: f64,
+    /// Blocking rules, most URLs blocked first; ties in line order.
+    pub hotspots: Vec<BlockedHotspot>,
+}
+
+/// Simulation results for a whole frontier.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationReport {
+    /// Per-host results, sorted by host.
+    pub hosts: Vec<HostReport>,
+    /// URLs whose host could not be determined; they are not simulated.
+    pub invalid_urls: usize,
+}
+
+impl SimulationReport {
+    /// Returns the number of allowed URLs across all hosts.
+    pub fn allowed_urls(&self) -> usize {
+        self.hosts.iter().map(|h| h.allowed_urls).sum()
+    }
+
+    /// Returns the number of blocked URLs across all hosts.
+    pub fn blocked_urls(&self) -> usize {
+        self.hosts.iter().map(|h| h.blocked_urls).sum()
+    }
+
+    /// Returns the expected crawl length assuming hosts are crawled in
+    /// parallel, i.e. the schedule of the slowest host.
+    pub fn schedule_secs(&self) -> f64 {
+        self.hosts
+            .iter()
+            .map(|h| h.schedule_secs)
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Simulates crawling `frontier_urls` as `agent`.
+///
+/// `robots_for_host` is called once per host and returns the host's
+/// robots.txt, or `None` if it has none. Hosts are lowercase and include a
+/// non-default port; IPv6 literals keep their brackets, as in
+/// `[2001:db8::1]:8080`.
+pub fn simulate_crawl<'a, I, F, S>(
+    frontier_urls: I,
+    agent: &str,
+    mut robots_for_host: F,
+) -> SimulationReport
+where
+    I: IntoIterator<Item = &'a str>,
+    F: FnMut(&str) -> Option<S>,
+    S: AsRef<str>,
+{
+    let mut by_host: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut report = SimulationReport::default();
+    for url in frontier_urls {
+        match host_of(url) {
+            Some(host) => by_host.entry(host).or_default().push(url),
+            None => report.invalid_urls += 1,
+        }
+    }
+
+    let matcher = RobotsMatcher::new();
+    for (host, urls) in by_host {
+        let robots = robots_for_host(&host);
+        let mut host_report = HostReport {
+            host,
+            has_robots: robots.is_some(),
+            allowed_urls: urls.len(),
+            blocked_urls: 0,
+            delay_secs: None,
+            schedule_secs: 0.0,
+            hotspots: Vec::new(),
+        };
+
+        if let Some(robots) = robots {
+            let robots = robots.as_ref();
+            let mut blocked_by_line: BTreeMap<i32, usize> = BTreeMap::new();
+            for url in &urls {
+                if !matcher.is_allowed(robots, agent, url) {
+                    *blocked_by_line.entry(matcher.matching_line()).or_default() += 1;
+                }
+            }
+            host_report.delay_secs = matcher
+                .crawl_delay()
+                .or_else(|| matcher.request_rate()?.interval_secs());
+
+            let mut hotspots: Vec<BlockedHotspot> = blocked_by_line
+                .into_iter()
+                .map(|(line, blocked_urls)| BlockedHotspot { line, blocked_urls })
+                .collect();
+            hotspots.sort_by_key(|h| Reverse(h.blocked_urls));
+            host_report.blocked_urls = hotspots.iter().map(|h| h.blocked_urls).sum();
+            host_report.allowed_urls -= host_report.blocked_urls;
+            host_report.hotspots = hotspots;
+        }
+
+        let delay = host_report.delay_secs.unwrap_or(0.0);
+        host_report.schedule_secs = host_report.allowed_urls as f64 * delay;
+        report.hosts.push(host_report);
+    }
+    report
+}
+
+/// Extracts the lowercase `host[:port]` part of an absolute URL.
+fn host_of(url: &str) -> Option<String> {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+        None => (String::new(), url.strip_prefix("//")?),
+    };
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = normalize_authority(&scheme, authority);
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate_crawl() {
+        let robots = "User-agent: *\nDisallow: /tmp/\nDisallow: /cgi-bin/\nCrawl-delay: 2\n";
+        let frontier = [
+            "https://example.com/",
+            "https://example.com/tmp/1",
+            "https://example.com/tmp/2",
+            "https://example.com/cgi-bin/x",
+            "https://Other.example/page",
+            "not a url",
+        ];
+        let report = simulate_crawl(frontier, "MyBot", |host| {
+            (host == "example.com").then_some(robots)
+        });
+
+        assert_eq!(report.invalid_urls, 1);
+        assert_eq!(report.hosts.len(), 2);
+        let host = &report.hosts[0];
+        assert_eq!(host.host, "example.com");
+        assert_eq!((host.allowed_urls, host.blocked_urls), (1, 3));
+        assert_eq!(host.delay_secs, Some(2.0));
+        assert_eq!(host.schedule_secs, 2.0);
+        assert_eq!(
+            host.hotspots[0],
+            BlockedHotspot {
+                line: 2,
+                blocked_urls: 2
+            }
+        );
+
+        let other = &report.hosts[1];
+        assert_eq!(other.host, "other.example");
+        assert!(!other.has_robots);
+        assert_eq!(other.allowed_urls, 1);
+        assert_eq!(report.schedule_secs(), 2.0);
+    }
+
+    #[test]
+    fn test_report_order_is_deterministic() {
+        let robots = "User-agent: *\nDisallow: /z/\nDisallow: /y/\nDisallow: /x/\n";
+        let frontier = [
+            "https://b.example/x/1",
+            "https://a.example/y/1",
+            "https://b.example/z/1",
+            "https://a.example/x/1",
+            "https://b.example/y/1",
+        ];
+        let report = simulate_crawl(frontier, "Bot", |_| Some(robots));
+        let hosts: Vec<&str> = report.hosts.iter().map(|h| h.host.as_str()).collect();
+        assert_eq!(hosts, ["a.example", "b.example"]);
+        let lines: Vec<i32> = report.hosts[1].hotspots.iter().map(|h| h.line).collect();
+        assert_eq!(lines, [2, 3, 4]);
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(
+            host_of("http://user@Host:8080/p?q").as_deref(),
+            Some("host:8080")
+        );
+        assert_eq!(host_of("//cdn.example/x").as_deref(), Some("cdn.example"));
+        assert_eq!(host_of("/relative"), None);
+        assert_eq!(
+            host_of("http://192.0.2.1:80/").as_deref(),
+            Some("192.0.2.1")
+        );
+        assert_eq!(
+            host_of("https://[2001:DB8::1]:8443/x").as_deref(),
+            Some("[2001:db8::1]:8443")
+        );
+        assert_eq!(host_of("//[::1]:80/x").as_deref(), Some("[::1]:80"));
+    }
+}
@@ -0,0 +1,148 @@
+//! Runtime lookup of C API functions that older `librobots` builds lack.
+//!
+//! The core matcher functions are linked normally. Functions added later
+//! (content signals, effective-group stats, matched rules, profiling,
+//! tracing, strict parsing, parse statistics, pre-parsed robots.txt) are resolved from the
+//! already loaded library on first use, so a binary built against a newer
+//! header still starts with an older library and the corresponding APIs
+//! degrade instead of failing with "undefined symbol".
+//!
+//! Lookup uses `dlsym`, or the runtime-loaded library with the `dlopen`
+//! feature; on platforms without either every optional function is
+//! reported as missing. A statically linked library (`vendored`, `static`
+//! or `ROBOTS_STATIC`) is as new as the binding, so its functions are
+//! linked directly; they are not exported for `dlsym` to find.
+
+use std::ffi::c_void;
+use std::os::raw::{c_char, c_int};
+#[cfg(not(robots_static))]
+use std::sync::OnceLock;
+
+use crate::explain::RawTraceRule;
+use crate::handler::RawCallbacks;
+use crate::lint::LineCallback;
+use crate::model::RawDirective;
+use crate::{ContentSignal, ParseStats, RawTiming, RobotsMatcherOpaque, RobotsTxtOpaque};
+
+macro_rules! optional_symbols {
+    ($(fn $name:ident($($ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            #[cfg(robots_static)]
+            pub(crate) fn $name() -> Option<unsafe extern "C" fn($($ty),*) -> $ret> {
+                extern "C" {
+                    fn $name($(_: $ty),*) -> $ret;
+                }
+                Some($name)
+            }
+
+            #[cfg(not(robots_static))]
+            pub(crate) fn $name() -> Option<unsafe extern "C" fn($($ty),*) -> $ret> {
+                static SYMBOL: OnceLock<Option<unsafe extern "C" fn($($ty),*) -> $ret>> =
+                    OnceLock::new();
+                if let Some(symbol) = SYMBOL.get() {
+                    return *symbol;
+                }
+                // Nothing is cached while no library is loaded, so a later
+                // successful load still finds the function.
+                let ptr = lookup(concat!(stringify!($name), "\0"))?;
+                // SAFETY: the symbol is declared with this signature in robots_c.h.
+                let symbol = (!ptr.is_null()).then(|| unsafe {
+                    std::mem::transmute::<*mut c_void, unsafe extern "C" fn($($ty),*) -> $ret>(ptr)
+                });
+                *SYMBOL.get_or_init(|| symbol)
+            }
+        )*
+
+        /// Names of the optional functions missing from the loaded library.
+        pub(crate) fn missing() -> Vec<&'static str> {
+            let mut missing = Vec::new();
+            $(
+                if $name().is_none() {
+                    missing.push(stringify!($name));
+                }
+            )*
+            missing
+        }
+    };
+}
+
+optional_symbols! {
+    fn robots_content_signal_supported() -> bool;
+    fn robots_has_content_signal(*const RobotsMatcherOpaque) -> bool;
+    fn robots_get_content_signal(*const RobotsMatcherOpaque, *mut ContentSignal) -> bool;
+    fn robots_allows_ai_train(*const RobotsMatcherOpaque) -> bool;
+    fn robots_allows_ai_input(*const RobotsMatcherOpaque) -> bool;
+    fn robots_allows_search(*const RobotsMatcherOpaque) -> bool;
+
+    fn robots_effective_rule_count(*const RobotsMatcherOpaque) -> usize;
+    fn robots_effective_rule_bytes(*const RobotsMatcherOpaque) -> usize;
+    fn robots_matching_rule(
+        *const RobotsMatcherOpaque,
+        *mut c_int,
+        *mut *const c_char,
+        *mut usize
+    ) -> bool;
+
+    fn robots_set_profiling(*mut RobotsMatcherOpaque, bool) -> ();
+    fn robots_get_timing(*const RobotsMatcherOpaque, *mut RawTiming) -> bool;
+
+    fn robots_set_tracing(*mut RobotsMatcherOpaque, bool) -> ();
+    fn robots_trace_rule_count(*const RobotsMatcherOpaque) -> usize;
+    fn robots_get_trace_rule(*const RobotsMatcherOpaque, usize, *mut RawTraceRule) -> bool;
+
+    fn robots_parse_stats(*const c_char, usize, *mut ParseStats) -> bool;
+    fn robots_parse(*const c_char, usize, *const RawCallbacks, *mut c_void) -> bool;
+    fn robots_parse_lines(*const c_char, usize, LineCallback, *mut c_void) -> bool;
+
+    fn robots_txt_parse(*const c_char, usize) -> *mut RobotsTxtOpaque;
+    fn robots_txt_parse_strict(*const c_char, usize) -> *mut RobotsTxtOpaque;
+    fn robots_set_strict(*mut RobotsMatcherOpaque, bool) -> ();
+    fn robots_txt_free(*mut RobotsTxtOpaque) -> ();
+    fn robots_txt_directive_count(*const RobotsTxtOpaque) -> usize;
+    fn robots_txt_get_directive(*const RobotsTxtOpaque, usize, *mut RawDirective) -> bool;
+    fn robots_txt_sitemap_count(*const RobotsTxtOpaque) -> usize;
+    fn robots_txt_sitemap(*const RobotsTxtOpaque, usize, *mut usize) -> *const c_char;
+    fn robots_allowed_by_parsed(
+        *mut RobotsMatcherOpaque,
+        *const RobotsTxtOpaque,
+        *const c_char,
+        usize,
+        *const c_char,
+        usize
+    ) -> bool;
+}
+
+#[cfg(feature = "dlopen")]
+use crate::dynamic::lookup;
+
+#[cfg(all(unix, not(any(feature = "dlopen", robots_static))))]
+fn lookup(name: &str) -> Option<*mut c_void> {
+    extern "C" {
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+    #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+    let rtld_default = -2isize as *mut c_void;
+    #[cfg(not(any(target_vendor = "apple", target_os = "freebsd")))]
+    let rtld_default = std::ptr::null_mut();
+    Some(unsafe { dlsym(rtld_default, name.as_ptr().cast()) })
+}
+
+#[cfg(not(any(unix, feature = "dlopen", robots_static)))]
+fn lookup(_name: &str) -> Option<*mut c_void> {
+    Some(std::ptr::null_mut())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        #[cfg(not(robots_static))]
+        {
+            assert!(!lookup("robots_version\0").unwrap().is_null());
+            assert!(lookup("robots_no_such_function\0").unwrap().is_null());
+        }
+        assert!(missing().is_empty());
+    }
+}
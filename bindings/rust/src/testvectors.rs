@@ -0,0 +1,232 @@
+//! Canonical robots.txt cases with their expected outcomes.
+//!
+//! The cases come from the examples in RFC 9309 and Google's robots.txt
+//! documentation, as exercised by the C++ library's own tests. Use them to
+//! check an integration, or another matcher, against this library's
+//! decisions.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::testvectors::{failures, VECTORS};
+//! use robotstxt::RobotsMatcher;
+//!
+//! let matcher = RobotsMatcher::new();
+//! let failed = failures(|v| matcher.is_allowed(v.robots_txt, v.user_agent, v.url));
+//! assert!(failed.is_empty(), "{} of {} cases failed", failed.len(), VECTORS.len());
+//! ```
+
+/// A single check and its expected decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestVector {
+    /// Short identifier, unique within [`VECTORS`].
+    pub name: &'static str,
+    /// Where the expectation comes from: an RFC 9309 section such as
+    /// `"RFC 9309 2.2.2"`, or `"Google"` for Google-specific behavior.
+    pub source: &'static str,
+    pub robots_txt: &'static str,
+    pub user_agent: &'static str,
+    pub url: &'static str,
+    /// Whether `url` is allowed for `user_agent`.
+    pub allowed: bool,
+}
+
+/// Returns the vectors for which `check` returns a different decision than
+/// expected, in [`VECTORS`] order.
+pub fn failures(mut check: impl FnMut(&TestVector) -> bool) -> Vec<&'static TestVector> {
+    VECTORS.iter().filter(|v| check(v) != v.allowed).collect()
+}
+
+const GROUPS: &str = "allow: /foo/bar/\n\n\
+                      user-agent: FooBot\ndisallow: /\nallow: /x/\n\
+                      user-agent: BarBot\ndisallow: /\nallow: /y/\n\n\n\
+                      allow: /w/\nuser-agent: BazBot\n\n\
+                      user-agent: FooBot\nallow: /z/\ndisallow: /\n";
+const FISH: &str = "user-agent: FooBot\ndisallow: /\nallow: /fish\n";
+const FISH_SLASH: &str = "user-agent: FooBot\ndisallow: /\nallow: /fish/\n";
+const PHP: &str = "user-agent: FooBot\ndisallow: /\nallow: /*.php\n";
+const PHP_END: &str = "user-agent: FooBot\ndisallow: /\nallow: /*.php$\n";
+const SPECIFIC_GROUP: &str = "User-agent: *\nDisallow: /x/\nUser-agent: FooBot\nDisallow: /y/\n";
+
+macro_rules! vectors {
+    ($($name:literal, $source:literal, $robots:expr, $agent:literal, $url:literal => $allowed:literal;)*) => {
+        /// All test vectors.
+        pub const VECTORS: &[TestVector] = &[
+            $(TestVector {
+                name: $name,
+                source: $source,
+                robots_txt: $robots,
+                user_agent: $agent,
+                url: $url,
+                allowed: $allowed,
+            },)*
+        ];
+    };
+}
+
+vectors! {
+    "empty-robots-allows", "Google", "", "FooBot", "" => true;
+    "empty-url-disallowed", "Google", "user-agent: FooBot\ndisallow: /\n", "FooBot", "" => false;
+
+    "line-syntax", "RFC 9309 2.1", "user-agent: FooBot\ndisallow: /\n", "FooBot", "http://foo.bar/x/y" => false;
+    "line-unknown-keys", "RFC 9309 2.1", "foo: FooBot\nbar: /\n", "FooBot", "http://foo.bar/x/y" => true;
+    "line-missing-colon", "Google", "user-agent FooBot\ndisallow /\n", "FooBot", "http://foo.bar/x/y" => false;
+
+    "groups-allow-x", "RFC 9309 2.1", GROUPS, "FooBot", "http://foo.bar/x/b" => true;
+    "groups-merged", "RFC 9309 2.1", GROUPS, "FooBot", "http://foo.bar/z/d" => true;
+    "groups-other-group", "RFC 9309 2.1", GROUPS, "FooBot", "http://foo.bar/y/c" => false;
+    "groups-rule-between-groups", "RFC 9309 2.1", GROUPS, "BarBot", "http://foo.bar/w/a" => true;
+    "groups-empty-group", "RFC 9309 2.1", GROUPS, "BazBot", "http://foo.bar/z/d" => true;
+    "groups-rule-outside-group", "RFC 9309 2.1", GROUPS, "FooBot", "http://foo.bar/foo/bar/" => false;
+    "groups-sitemap-not-separator", "RFC 9309 2.1",
+        "User-agent: BarBot\nSitemap: https://foo.bar/sitemap\nUser-agent: *\nDisallow: /\n",
+        "BarBot", "http://foo.bar/" => false;
+    "groups-unknown-not-separator", "RFC 9309 2.1",
+        "User-agent: FooBot\nInvalid-Unknown-Line: unknown\nUser-agent: *\nDisallow: /\n",
+        "FooBot", "http://foo.bar/" => false;
+
+    "keys-case-insensitive", "RFC 9309 2.1", "uSeR-aGeNt: FooBot\nAlLoW: /x/\ndIsAlLoW: /\n",
+        "FooBot", "http://foo.bar/a/b" => false;
+    "agent-case-insensitive", "RFC 9309 2.2.1", "User-Agent: FOO BAR\nAllow: /x/\nDisallow: /\n",
+        "foo", "http://foo.bar/x/y" => true;
+    "agent-up-to-space", "Google", "User-Agent: *\nDisallow: /\nUser-Agent: Foo Bar\nAllow: /x/\nDisallow: /\n",
+        "Foo", "http://foo.bar/x/y" => true;
+    "global-group-fallback", "RFC 9309 2.2.1", "user-agent: *\nallow: /\nuser-agent: FooBot\ndisallow: /\n",
+        "BarBot", "http://foo.bar/x/y" => true;
+    "global-group-specific", "RFC 9309 2.2.1", "user-agent: *\nallow: /\nuser-agent: FooBot\ndisallow: /\n",
+        "FooBot", "http://foo.bar/x/y" => false;
+    "no-matching-group", "RFC 9309 2.2.1", "user-agent: FooBot\nallow: /\nuser-agent: BarBot\ndisallow: /\n",
+        "QuxBot", "http://foo.bar/x/y" => true;
+    "specific-group-replaces-global", "RFC 9309 2.2.1", SPECIFIC_GROUP, "FooBot", "http://foo.bar/x/page" => true;
+    "specific-group-rule", "RFC 9309 2.2.1", SPECIFIC_GROUP, "FooBot", "http://foo.bar/y/page" => false;
+
+    "path-case-sensitive", "RFC 9309 2.2.2", "user-agent: FooBot\ndisallow: /X/\n", "FooBot", "http://foo.bar/x/y" => true;
+    "longest-match-disallow", "RFC 9309 2.2.2", "user-agent: FooBot\ndisallow: /x/page.html\nallow: /x/\n",
+        "FooBot", "http://foo.bar/x/page.html" => false;
+    "longest-match-allow", "RFC 9309 2.2.2", "user-agent: FooBot\nallow: /x/page.html\ndisallow: /x/\n",
+        "FooBot", "http://foo.bar/x/page.html" => true;
+    "equal-match-allow-wins", "RFC 9309 2.2.2", "user-agent: FooBot\ndisallow: /\nallow: /\n",
+        "FooBot", "http://foo.bar/x/page.html" => true;
+    "trailing-slash-shorter", "RFC 9309 2.2.2", "user-agent: FooBot\ndisallow: /x\nallow: /x/\n",
+        "FooBot", "http://foo.bar/x" => false;
+    "wildcard-longer-wins", "RFC 9309 2.2.2", "user-agent: FooBot\nallow: /page\ndisallow: /*.html\n",
+        "FooBot", "http://foo.bar/page.html" => false;
+
+    "encoding-utf8-rule", "RFC 9309 2.2.2", "User-agent: FooBot\nDisallow: /\nAllow: /foo/bar/\u{30c4}\n",
+        "FooBot", "http://foo.bar/foo/bar/%E3%83%84" => true;
+    "encoding-unreserved-decoded", "RFC 9309 2.2.2", "User-agent: FooBot\nDisallow: /\nAllow: /foo/bar/%62%61%7A\n",
+        "FooBot", "http://foo.bar/foo/bar/baz" => true;
+    "escaped-asterisk-literal", "RFC 9309 2.2.3", "User-agent: FooBot\nDisallow: /path/file-with-%2A.html\n",
+        "FooBot", "http://foo.bar/path/file-with-*.html" => false;
+    "escaped-asterisk-not-wildcard", "RFC 9309 2.2.3", "User-agent: FooBot\nDisallow: /path/file-with-%2A.html\n",
+        "FooBot", "http://foo.bar/path/file-with-x.html" => true;
+    "escaped-dollar-not-anchor", "RFC 9309 2.2.3", "User-agent: FooBot\nDisallow: /path/price%24.html\n",
+        "FooBot", "http://foo.bar/path/price" => true;
+
+    "wildcard-middle", "RFC 9309 2.2.3", "User-agent: FooBot\nDisallow: /foo/bar/quz\nAllow: /foo/*/qux\n",
+        "FooBot", "http://foo.bar/foo/bar/quz" => false;
+    "end-anchor", "RFC 9309 2.2.3", "User-agent: FooBot\nDisallow: /foo/bar$\nAllow: /foo/bar/qux\n",
+        "FooBot", "http://foo.bar/foo/bar" => false;
+    "end-anchor-longer-path", "RFC 9309 2.2.3", "User-agent: FooBot\nDisallow: /foo/bar$\nAllow: /foo/bar/qux\n",
+        "FooBot", "http://foo.bar/foo/bar/baz" => true;
+    "comment", "RFC 9309 2.2.3", "User-agent: FooBot\n# Disallow: /\nDisallow: /foo/quz#qux\nAllow: /\n",
+        "FooBot", "http://foo.bar/foo/quz" => false;
+
+    "index-html-is-directory", "Google", "User-Agent: *\nAllow: /allowed-slash/index.html\nDisallow: /\n",
+        "foobot", "http://foo.com/allowed-slash/" => true;
+    "index-htm-not-equal", "Google", "User-Agent: *\nAllow: /allowed-slash/index.html\nDisallow: /\n",
+        "foobot", "http://foo.com/allowed-slash/index.htm" => false;
+
+    "doc-fish-prefix", "Google", FISH, "FooBot", "http://foo.bar/fishheads/yummy.html" => true;
+    "doc-fish-query", "Google", FISH, "FooBot", "http://foo.bar/fish.html?id=anything" => true;
+    "doc-fish-case", "Google", FISH, "FooBot", "http://foo.bar/Fish.asp" => false;
+    "doc-fish-not-prefix", "Google", FISH, "FooBot", "http://foo.bar/catfish" => false;
+    "doc-fish-in-query", "Google", FISH, "FooBot", "http://foo.bar/?id=fish" => false;
+    "doc-fish-star", "Google", "user-agent: FooBot\ndisallow: /\nallow: /fish*\n",
+        "FooBot", "http://foo.bar/fish/salmon.html" => true;
+    "doc-fish-slash", "Google", FISH_SLASH, "FooBot", "http://foo.bar/fish/?salmon" => true;
+    "doc-fish-slash-bare", "Google", FISH_SLASH, "FooBot", "http://foo.bar/fish" => false;
+    "doc-php-anywhere", "Google", PHP, "FooBot", "http://foo.bar//folder/any.php.file.html" => true;
+    "doc-php-in-query", "Google", PHP, "FooBot", "http://foo.bar/index?f=filename.php/" => true;
+    "doc-php-case", "Google", PHP, "FooBot", "http://foo.bar/windows.PHP" => false;
+    "doc-php-end", "Google", PHP_END, "FooBot", "http://foo.bar/folder/filename.php" => true;
+    "doc-php-end-query", "Google", PHP_END, "FooBot", "http://foo.bar/filename.php?parameters" => false;
+    "doc-php-end-suffix", "Google", PHP_END, "FooBot", "http://foo.bar/filename.php5" => false;
+    "doc-fish-star-php", "Google", "user-agent: FooBot\ndisallow: /\nallow: /fish*.php\n",
+        "FooBot", "http://foo.bar/fishheads/catfish.php?parameters" => true;
+    "precedence-longer-allow", "Google", "user-agent: FooBot\nallow: /p\ndisallow: /\n",
+        "FooBot", "http://example.com/page" => true;
+    "precedence-equal", "Google", "user-agent: FooBot\nallow: /folder\ndisallow: /folder\n",
+        "FooBot", "http://example.com/folder/page" => true;
+    "precedence-wildcard", "Google", "user-agent: FooBot\nallow: /page\ndisallow: /*.htm\n",
+        "FooBot", "http://example.com/page.htm" => false;
+    "precedence-root-only", "Google", "user-agent: FooBot\nallow: /$\ndisallow: /\n",
+        "FooBot", "http://example.com/page.html" => false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RobotsMatcher, RobotsTxt};
+    use std::collections::HashSet;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_vectors_match_library() {
+        let matcher = RobotsMatcher::new();
+        let failed = failures(|v| matcher.is_allowed(v.robots_txt, v.user_agent, v.url));
+        assert!(failed.is_empty(), "{failed:#?}");
+
+        let names: HashSet<_> = VECTORS.iter().map(|v| v.name).collect();
+        assert_eq!(names.len(), VECTORS.len());
+        assert_eq!(failures(|v| !v.allowed).len(), VECTORS.len());
+    }
+
+    /// Checks per second below which the throughput tests fail, unless
+    /// overridden by the environment variable of this name.
+    const PERF_FLOOR_ENV: &str = "ROBOTSTXT_PERF_FLOOR";
+    const DEFAULT_PERF_FLOOR: f64 = 100_000.0;
+
+    /// Runs `check` over every vector, repeatedly for about a second, and
+    /// fails if fewer than the floor of checks per second complete.
+    fn assert_throughput(what: &str, mut check: impl FnMut(&TestVector) -> bool) {
+        let floor = match std::env::var(PERF_FLOOR_ENV) {
+            Ok(value) => value
+                .parse::<f64>()
+                .unwrap_or_else(|_| panic!("{PERF_FLOOR_ENV}={value:?} is not a number")),
+            Err(_) => DEFAULT_PERF_FLOOR,
+        };
+        // Warm up the library and caches before timing.
+        for v in VECTORS {
+            check(v);
+        }
+        let start = Instant::now();
+        let mut checks = 0;
+        while start.elapsed() < Duration::from_secs(1) {
+            for v in VECTORS {
+                assert_eq!(check(v), v.allowed, "{}", v.name);
+            }
+            checks += VECTORS.len();
+        }
+        let rate = checks as f64 / start.elapsed().as_secs_f64();
+        assert!(
+            rate >= floor,
+            "{what}: {rate:.0} checks/s, below the floor of {floor:.0} (set {PERF_FLOOR_ENV})"
+        );
+    }
+
+    /// Parses and matches each vector from scratch, with a matcher and with
+    /// a parsed `RobotsTxt`. Run with `cargo test --release -- --ignored`;
+    /// set `ROBOTSTXT_PERF_FLOOR` to tune the floor for the machine.
+    #[test]
+    #[ignore]
+    fn perf_parse_and_match_throughput() {
+        let matcher = RobotsMatcher::new();
+        assert_throughput("RobotsMatcher::is_allowed", |v| {
+            matcher.is_allowed(v.robots_txt, v.user_agent, v.url)
+        });
+        assert_throughput("RobotsTxt::parse and is_allowed", |v| {
+            RobotsTxt::parse(v.robots_txt).is_allowed(v.user_agent, v.url)
+        });
+    }
+}
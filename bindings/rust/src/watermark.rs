@@ -0,0 +1,139 @@
+//! Provenance stamps for stored decisions and audit records.
+//!
+//! A [`Watermark`] records which crate, library and configuration made a
+//! decision about which robots.txt, so a stored record can still be
+//! interpreted after an upgrade or a robots.txt change. Wrap a record in
+//! [`Watermarked`] before storing it; with the `serde` feature the stamp
+//! serializes next to the record's own fields.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use robotstxt::watermark::Watermark;
+//! use robotstxt::RobotsMatcher;
+//!
+//! let robots_txt = "User-agent: *\nDisallow: /admin/\n";
+//! let matcher = RobotsMatcher::new();
+//! let result = matcher.check(robots_txt, "MyBot", "https://example.com/admin/");
+//! let record = Watermark::new(robots_txt, &matcher).wrap(result);
+//! assert_eq!(record.watermark.crate_version, env!("CARGO_PKG_VERSION"));
+//! ```
+
+use crate::{capabilities, RobotsMatcher, UrlLengthPolicy};
+
+/// Which code, input and configuration produced a record.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Watermark {
+    /// Version of this crate.
+    pub crate_version: String,
+    /// Version of the robots library in use.
+    pub library_version: String,
+    /// How the library is linked: `vendored`, `static`, `dlopen` or
+    /// `shared`.
+    pub backend: String,
+    /// FNV-1a 64 hash of the robots.txt bytes, as 16 hex digits. Identifies
+    /// the file; it is not a cryptographic digest.
+    pub robots_hash: String,
+    /// FNV-1a 64 hash of the matcher options that affect decisions, as 16
+    /// hex digits: strict mode and the URL limit.
+    pub config_fingerprint: String,
+}
+
+impl Watermark {
+    /// Stamps a decision about `robots_txt` made by `matcher`.
+    pub fn new(robots_txt: &str, matcher: &RobotsMatcher) -> Watermark {
+        let caps = capabilities();
+        let backend = if caps.vendored {
+            "vendored"
+        } else if caps.static_link {
+            "static"
+        } else if caps.dlopen {
+            "dlopen"
+        } else {
+            "shared"
+        };
+        let url_limit = match matcher.url_limit.get() {
+            Some(limit) => {
+                let policy = match limit.policy {
+                    UrlLengthPolicy::Truncate => "truncate",
+                    UrlLengthPolicy::Reject => "reject",
+                };
+                format!("{}/{policy}", limit.max_len)
+            }
+            None => "none".to_string(),
+        };
+        let config = format!("strict={};url_limit={url_limit}", matcher.is_strict());
+        Watermark {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            library_version: caps.library_version,
+            backend: backend.to_string(),
+            robots_hash: hex_hash(robots_txt.as_bytes()),
+            config_fingerprint: hex_hash(config.as_bytes()),
+        }
+    }
+
+    /// Attaches the stamp to `record`.
+    pub fn wrap<T>(self, record: T) -> Watermarked<T> {
+        Watermarked {
+            watermark: self,
+            record,
+        }
+    }
+}
+
+/// A record with the [`Watermark`] of the decision behind it.
+///
+/// With the `serde` feature, the record's fields and a `watermark` field
+/// serialize side by side in one object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Watermarked<T> {
+    pub watermark: Watermark,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub record: T,
+}
+
+/// FNV-1a 64 of `bytes` as 16 lowercase hex digits. Unlike std's hashers,
+/// the result is fixed across Rust releases.
+fn hex_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UrlLimit;
+
+    #[test]
+    fn test_watermark() {
+        assert_eq!(hex_hash(b""), "cbf29ce484222325");
+        assert_eq!(hex_hash(b"a"), "af63dc4c8601ec8c");
+
+        let matcher = RobotsMatcher::new();
+        let robots = "User-agent: *\nDisallow: /x\n";
+        let watermark = Watermark::new(robots, &matcher);
+        assert_eq!(watermark.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(watermark.library_version, crate::version());
+        assert_eq!(watermark.robots_hash, hex_hash(robots.as_bytes()));
+        assert_ne!(
+            Watermark::new("", &matcher).robots_hash,
+            watermark.robots_hash
+        );
+
+        matcher.set_url_limit(Some(UrlLimit {
+            max_len: 10,
+            policy: UrlLengthPolicy::Reject,
+        }));
+        let limited = Watermark::new(robots, &matcher);
+        assert_ne!(limited.config_fingerprint, watermark.config_fingerprint);
+        assert_eq!(limited.robots_hash, watermark.robots_hash);
+
+        let record = limited.clone().wrap(matcher.check(robots, "FooBot", "/x"));
+        assert_eq!(record.watermark, limited);
+        assert!(!record.record.allowed);
+    }
+}
@@ -1137,24 +1137,42 @@ void RobotsMatcher::HandleUnknownAction(int line_num, std::string_view action,
 
 void ParsedRobotsKey::Parse(std::string_view key, bool* is_acceptable_typo) {
   key_text_ = std::string_view();
-  if (KeyIsUserAgent(key, is_acceptable_typo)) {
-    type_ = USER_AGENT;
-  } else if (KeyIsAllow(key, is_acceptable_typo)) {
-    type_ = ALLOW;
-  } else if (KeyIsDisallow(key, is_acceptable_typo)) {
-    type_ = DISALLOW;
-  } else if (KeyIsSitemap(key, is_acceptable_typo)) {
-    type_ = SITEMAP;
-  } else if (KeyIsCrawlDelay(key, is_acceptable_typo)) {
-    type_ = CRAWL_DELAY;
-  } else if (KeyIsRequestRate(key, is_acceptable_typo)) {
-    type_ = REQUEST_RATE;
+  *is_acceptable_typo = false;
+  type_ = UNKNOWN;
+  // Dispatch on the first character so each line is compared against a
+  // single family of keys. Every spelling of a key, accepted typos included,
+  // starts with the same character, so this recognizes exactly the same keys
+  // as trying each KeyIs* in turn.
+  switch (key.empty() ? '\0' : AsciiToLower(key[0])) {
+    case 'u':
+      if (KeyIsUserAgent(key, is_acceptable_typo)) type_ = USER_AGENT;
+      break;
+    case 'a':
+      if (KeyIsAllow(key, is_acceptable_typo)) type_ = ALLOW;
+      break;
+    case 'd':
+      if (KeyIsDisallow(key, is_acceptable_typo)) type_ = DISALLOW;
+      break;
+    case 's':
+      if (KeyIsSitemap(key, is_acceptable_typo)) type_ = SITEMAP;
+      break;
+    case 'c':
+      if (KeyIsCrawlDelay(key, is_acceptable_typo)) {
+        type_ = CRAWL_DELAY;
 #if ROBOTS_SUPPORT_CONTENT_SIGNAL
-  } else if (KeyIsContentSignal(key, is_acceptable_typo)) {
-    type_ = CONTENT_SIGNAL;
+      } else if (KeyIsContentSignal(key, is_acceptable_typo)) {
+        type_ = CONTENT_SIGNAL;
 #endif  // ROBOTS_SUPPORT_CONTENT_SIGNAL
-  } else {
-    type_ = UNKNOWN;
+      }
+      break;
+    case 'r':
+      if (KeyIsRequestRate(key, is_acceptable_typo)) type_ = REQUEST_RATE;
+      break;
+    default:
+      break;
+  }
+  if (type_ == UNKNOWN) {
+    *is_acceptable_typo = false;
     key_text_ = key;
   }
 }
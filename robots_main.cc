@@ -20,7 +20,8 @@
 // according to records found in a local robots.txt file, based on Google's 
 // robots.txt parsing and matching algorithms.
 // Usage:
-//     robots_main <local_path_to_robotstxt> <user_agent> <url>
+//     robots_main [--porcelain | --quiet] <local_path_to_robotstxt>
+//                 <user_agent> <url>
 // Arguments:
 // local_path_to_robotstxt: local path to a file containing robots.txt records.
 //   For example: /home/users/username/robots.txt
@@ -34,11 +35,16 @@
 // to access 'url' based on records in 'local_path_to_robotstxt'. When multiple 
 // user agents are provided, check them as a vector based on functionality in 
 // "AllowedByRobots" method.
+// Options:
+// --porcelain: print a single line "<ALLOWED|DISALLOWED>\t<user_agent>\t<url>"
+//   instead of the sentence. This format is stable and meant for scripts.
+// --quiet, -q: print nothing; only the return code reports the verdict.
 // Return code:
 //   0 when the url is ALLOWED for the user_agent.
 //   1 when the url is DISALLOWED for the user_agent.
 //   2 when --help is requested or if there is something invalid in the flags
 //   passed.
+//   3 when the robots.txt file cannot be read.
 //
 #include <fstream>
 #include <iostream>
@@ -86,40 +92,80 @@ void ShowHelp(int argc, char** argv) {
             << std::endl;
   std::cerr << "The URI must be %-encoded according to RFC3986." << std::endl
             << std::endl;
+  std::cerr << "Options: " << std::endl
+            << "  --porcelain  print \"<ALLOWED|DISALLOWED>\\t<user_agent>"
+            << "\\t<URI>\" only" << std::endl
+            << "  --quiet, -q  print nothing, report the verdict via the exit"
+            << " code" << std::endl
+            << std::endl;
+  std::cerr << "Exit codes: 0 allowed, 1 disallowed, 2 usage error,"
+            << " 3 robots.txt file could not be read" << std::endl
+            << std::endl;
   std::cerr << "Example: " << std::endl
             << "  " << argv[0] << " robots.txt FooBot http://example.com/foo"
             << std::endl;
 }
 
+enum ExitCode {
+  kAllowed = 0,
+  kDisallowed = 1,
+  kUsageError = 2,
+  kReadError = 3,
+};
+
 int main(int argc, char** argv) {
-  std::string filename = argc >= 2 ? argv[1] : "";
+  bool porcelain = false;
+  bool quiet = false;
+  std::vector<std::string> args;
+  for (int i = 1; i < argc; ++i) {
+    std::string arg = argv[i];
+    if (arg == "--porcelain") {
+      porcelain = true;
+    } else if (arg == "--quiet" || arg == "-q") {
+      quiet = true;
+    } else {
+      args.push_back(arg);
+    }
+  }
+  std::string filename = !args.empty() ? args[0] : "";
   if (filename == "-h" || filename == "-help" || filename == "--help") {
     ShowHelp(argc, argv);
-    return 2;
+    return kUsageError;
   }
-  if (argc != 4) {
+  if (args.size() != 3) {
     std::cerr << "Invalid amount of arguments. Showing help." << std::endl
               << std::endl;
     ShowHelp(argc, argv);
-    return 2;
+    return kUsageError;
+  }
+  if (porcelain && quiet) {
+    std::cerr << "--porcelain and --quiet are mutually exclusive." << std::endl;
+    return kUsageError;
   }
   std::string robots_content;
   if (!(LoadFile(filename, &robots_content))) {
-    std::cerr << "failed to read file \"" << filename << "\"" << std::endl;
-    return 2;
+    if (!quiet) {
+      std::cerr << "failed to read file \"" << filename << "\"" << std::endl;
+    }
+    return kReadError;
   }
 
-  std::vector<std::string> useragents = SplitString(argv[2], ',');
+  std::vector<std::string> useragents = SplitString(args[1], ',');
   googlebot::RobotsMatcher matcher;
-  std::string url = argv[3];
+  std::string url = args[2];
   bool allowed = matcher.AllowedByRobots(robots_content, &useragents, url);
+  const char* verdict = allowed ? "ALLOWED" : "DISALLOWED";
 
-  std::cout << "user-agent '" << argv[2] << "' with URI '" << url
-            << "': " << (allowed ? "ALLOWED" : "DISALLOWED") << std::endl;
-  if (robots_content.empty()) {
-    std::cout << "notice: robots file is empty so all user-agents are allowed"
-              << std::endl;
+  if (porcelain) {
+    std::cout << verdict << '\t' << args[1] << '\t' << url << std::endl;
+  } else if (!quiet) {
+    std::cout << "user-agent '" << args[1] << "' with URI '" << url
+              << "': " << verdict << std::endl;
+    if (robots_content.empty()) {
+      std::cout << "notice: robots file is empty so all user-agents are allowed"
+                << std::endl;
+    }
   }
 
-  return allowed ? 0 : 1;
+  return allowed ? kAllowed : kDisallowed;
 }